@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::contextual_speed_and_pace_model::{MODEL_VERSION, RankContext, derive_race_meta_with_context, params_hash};
+use crate::models::backtest::{
+    BacktestRace, BacktestRaceOutcome, BacktestReport, BacktestStats, FlatStakeStats,
+};
+use crate::models::model_config::ModelConfig;
+use crate::models::race_result::{RaceResult, ResultStarter};
+use crate::models::racecard::Race;
+use crate::utils::transformers::Transformers;
+
+pub(crate) fn normalize_program(program_number: &str) -> String {
+    program_number.trim().to_uppercase()
+}
+
+// Horses on the card that do not appear among the linked result starters did not run.
+pub(crate) fn apply_result_scratches(race: &Race, result: &RaceResult) -> Race {
+    let mut race = race.clone();
+    let started: HashSet<i64> = result.starters.iter().filter_map(|s| s.horse_id).collect();
+
+    if !started.is_empty() {
+        for h in &mut race.horses {
+            if !started.contains(&h.id) {
+                h.scratched = true;
+            }
+        }
+    }
+
+    race
+}
+
+// Payoff per $2, falling back to final odds when the chart has no payoff.
+fn win_payoff(winner: &ResultStarter) -> Option<f64> {
+    match (winner.win_payoff, winner.final_odds) {
+        (Some(payoff), _) => Some(payoff),
+        (None, Some(odds)) => Some((odds + 1.0) * 2.0),
+        (None, None) => None,
+    }
+}
+
+fn win_return(payoff: Option<f64>, stake: f64) -> f64 {
+    payoff.map(|p| p / 2.0 * stake).unwrap_or(0.0)
+}
+
+fn record_bet(stats: &mut FlatStakeStats, won: bool, stake: f64, returned: f64) {
+    stats.bets += 1;
+    stats.staked += stake;
+    if won {
+        stats.wins += 1;
+        stats.returned += returned;
+    }
+}
+
+fn finish_flat_stake(stats: &mut FlatStakeStats) {
+    stats.profit = stats.returned - stats.staked;
+    stats.hit_rate = if stats.bets > 0 { stats.wins as f64 / stats.bets as f64 } else { 0.0 };
+    stats.roi = if stats.staked > 0.0 { stats.profit / stats.staked } else { 0.0 };
+}
+
+fn record_outcome(stats: &mut BacktestStats, outcome: &BacktestRaceOutcome, stake: f64) {
+    stats.races += 1;
+    if outcome.top_pick.is_some() {
+        let returned = win_return(outcome.top_pick_payoff, stake);
+        record_bet(&mut stats.top_pick, outcome.top_pick_won, stake, returned);
+    }
+    if outcome.win_bet.is_some() {
+        let returned = win_return(outcome.win_bet_payoff, stake);
+        record_bet(&mut stats.win_bet, outcome.win_bet_won, stake, returned);
+    }
+}
+
+fn finish_stats(stats: &mut BacktestStats) {
+    finish_flat_stake(&mut stats.top_pick);
+    finish_flat_stake(&mut stats.win_bet);
+}
+
+pub fn backtest_race(entry: &BacktestRace, config: &ModelConfig) -> BacktestRaceOutcome {
+    let race = apply_result_scratches(&entry.race, &entry.result);
+    let racecard_date = Transformers::yyyymmdd_to_mmddyyyy(&entry.date);
+    let ctx = RankContext {
+        racecard_date: racecard_date.as_deref(),
+        track_code: Some(&entry.track_code),
+        sire_stats: &entry.sire_stats,
+        racecard: entry.racecard.as_deref(),
+    };
+    let meta = derive_race_meta_with_context(&race, &ctx, config);
+
+    let scratched: HashSet<String> = race
+        .horses
+        .iter()
+        .filter(|h| h.scratched)
+        .map(|h| normalize_program(&h.program_number))
+        .collect();
+
+    let top_pick = meta
+        .race_rank_result
+        .horses
+        .iter()
+        .filter(|h| h.score.map(|s| s.is_finite()).unwrap_or(false))
+        .find(|h| !scratched.contains(&normalize_program(&h.program_number)))
+        .map(|h| h.program_number.clone());
+    let win_bet = meta.win_bet.as_ref().map(|w| w.program_number.clone());
+
+    let winners = entry.result.winners();
+    let winning = |program: &Option<String>| {
+        let program = normalize_program(program.as_deref()?);
+        winners.iter().copied().find(|w| normalize_program(&w.program_number) == program)
+    };
+    let top_pick_winner = winning(&top_pick);
+    let win_bet_winner = winning(&win_bet);
+
+    BacktestRaceOutcome {
+        race_id: race.id,
+        track_code: entry.track_code.clone(),
+        date: entry.date.clone(),
+        race_number: race.race_number,
+        surface_mode: meta.race_rank_result.surface_mode,
+        shape: meta.shape,
+        confidence: meta.confidence,
+        top_pick_won: top_pick_winner.is_some(),
+        win_bet_won: win_bet_winner.is_some(),
+        top_pick,
+        win_bet,
+        winners: winners.iter().map(|w| w.program_number.clone()).collect(),
+        top_pick_payoff: top_pick_winner.and_then(win_payoff),
+        win_bet_payoff: win_bet_winner.and_then(win_payoff),
+    }
+}
+
+pub fn run_backtest(races: &[BacktestRace], config: &ModelConfig, stake: f64) -> BacktestReport {
+    let mut overall = BacktestStats::default();
+    let mut by_confidence: BTreeMap<String, BacktestStats> = BTreeMap::new();
+    let mut by_shape: BTreeMap<String, BacktestStats> = BTreeMap::new();
+    let mut by_surface: BTreeMap<String, BacktestStats> = BTreeMap::new();
+    let mut by_track: BTreeMap<String, BacktestStats> = BTreeMap::new();
+    let mut outcomes = Vec::with_capacity(races.len());
+
+    for entry in races {
+        let outcome = backtest_race(entry, config);
+
+        record_outcome(&mut overall, &outcome, stake);
+        for (buckets, key) in [
+            (&mut by_confidence, format!("{:?}", outcome.confidence)),
+            (&mut by_shape, format!("{:?}", outcome.shape)),
+            (&mut by_surface, format!("{:?}", outcome.surface_mode)),
+            (&mut by_track, outcome.track_code.to_uppercase()),
+        ] {
+            record_outcome(buckets.entry(key).or_default(), &outcome, stake);
+        }
+
+        outcomes.push(outcome);
+    }
+
+    finish_stats(&mut overall);
+    for buckets in [&mut by_confidence, &mut by_shape, &mut by_surface, &mut by_track] {
+        buckets.values_mut().for_each(finish_stats);
+    }
+
+    BacktestReport {
+        model_version: MODEL_VERSION.to_string(),
+        params_hash: params_hash(config),
+        stake,
+        overall,
+        by_confidence,
+        by_shape,
+        by_surface,
+        by_track,
+        races: outcomes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::models::model_config::ConnectionsConfig;
+    use crate::models::racecard::{Horse, PastPerformance, Racecard};
+
+    fn horse(id: i64, program_number: &str, speed: u32) -> Horse {
+        let pp = PastPerformance {
+            race_date: "12/01/2025".to_string(),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            bris_speed_rating: Some(speed),
+            bris_4f_pace: Some(speed),
+            bris_late_pace: Some(speed),
+            ..Default::default()
+        };
+        Horse {
+            id,
+            program_number: program_number.to_string(),
+            horse_name: format!("Horse {program_number}"),
+            past_performances: vec![pp; 3],
+            ..Default::default()
+        }
+    }
+
+    fn race(race_number: u32, horses: Vec<Horse>) -> Race {
+        Race {
+            id: race_number as i64,
+            race_number: Some(race_number),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            four_f_bris_pace_par: Some(85),
+            bris_speed_for_class: Some(85),
+            bris_late_pace_par: Some(85),
+            horses,
+            ..Default::default()
+        }
+    }
+
+    fn starter(horse_id: i64, program_number: &str, finish_position: u32) -> ResultStarter {
+        ResultStarter {
+            horse_id: Some(horse_id),
+            program_number: program_number.to_string(),
+            finish_position: Some(finish_position),
+            ..Default::default()
+        }
+    }
+
+    fn entry(race: Race, starters: Vec<ResultStarter>) -> BacktestRace {
+        BacktestRace {
+            racecard_id: 1,
+            track_code: "SAR".to_string(),
+            date: "20260101".to_string(),
+            race,
+            result: RaceResult { starters, ..Default::default() },
+            racecard: None,
+            sire_stats: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn scores_the_top_pick_against_the_chart() {
+        let mut winner = starter(1, "1", 1);
+        winner.win_payoff = Some(6.6);
+        let race = race(1, vec![horse(1, "1", 95), horse(2, "2", 80), horse(3, "3", 75)]);
+        let entry = entry(race, vec![winner, starter(2, "2", 2), starter(3, "3", 3)]);
+
+        let outcome = backtest_race(&entry, &ModelConfig::default());
+        assert_eq!(outcome.top_pick.as_deref(), Some("1"));
+        assert!(outcome.top_pick_won);
+        assert_eq!(outcome.top_pick_payoff, Some(6.6));
+        assert_eq!(outcome.winners, vec!["1"]);
+
+        let report = run_backtest(&[entry], &ModelConfig::default(), 2.0);
+        assert_eq!((report.overall.top_pick.bets, report.overall.top_pick.wins), (1, 1));
+        assert!((report.overall.top_pick.roi - 2.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn horses_missing_from_the_chart_are_scratched() {
+        let mut winner = starter(2, "2", 1);
+        winner.final_odds = Some(4.0);
+        let race = race(1, vec![horse(1, "1", 95), horse(2, "2", 80)]);
+
+        let outcome = backtest_race(&entry(race, vec![winner]), &ModelConfig::default());
+        assert_eq!(outcome.top_pick.as_deref(), Some("2"));
+        assert_eq!(outcome.top_pick_payoff, Some(10.0));
+    }
+
+    #[test]
+    fn ranks_with_the_card_context() {
+        let mut switched = horse(1, "1", 85);
+        switched.todays_jockey = "New Rider".to_string();
+        switched.jockey_starts_current_year = Some(100);
+        switched.jockey_wins_current_year = Some(25);
+        for pp in &mut switched.past_performances {
+            pp.jockey = "Old Rider".to_string();
+        }
+        let mut old_rider_mount = horse(9, "1", 80);
+        old_rider_mount.todays_jockey = "Old Rider".to_string();
+        old_rider_mount.jockey_starts_current_year = Some(100);
+        old_rider_mount.jockey_wins_current_year = Some(5);
+
+        // Level on figures, so only the rider change can separate them.
+        let today = race(1, vec![horse(2, "2", 85), switched]);
+        let card = Racecard { races: vec![today.clone(), race(2, vec![old_rider_mount])], ..Default::default() };
+        let config = ModelConfig { connections: Some(ConnectionsConfig::default()), ..ModelConfig::default() };
+        let starters = vec![starter(1, "1", 1), starter(2, "2", 2)];
+
+        let without_card = backtest_race(&entry(today.clone(), starters.clone()), &config);
+        let with_card = backtest_race(&BacktestRace { racecard: Some(Arc::new(card)), ..entry(today, starters) }, &config);
+
+        assert_eq!(without_card.top_pick.as_deref(), Some("2"));
+        assert_eq!(with_card.top_pick.as_deref(), Some("1"));
+    }
+}
@@ -0,0 +1,220 @@
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::models::class_analysis::{ClassChange, ClassDirection, ClassType, RaceClass};
+use crate::models::racecard::{Horse, Race};
+
+const SAME_CLASS_TOLERANCE: f64 = 2.0;
+
+static RE_GRADE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"g([123])\b").unwrap());
+// A standalone price such as "25000", "$62,500" or "62.5k"; "3yo" and "n2l" are not prices.
+static RE_PRICE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[^a-z0-9.])\$?(\d+(?:\.\d+)?)(k)?").unwrap());
+// Age and sex restrictions ("3yo", "3&up", "4up f&m") that can precede the class.
+static RE_RESTRICTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:(?:\d\s*(?:yo|&?\s*up)|f\s*&\s*m|fm?)\b\s*)+").unwrap());
+static RE_CONDITION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"n(\d)([lx])").unwrap());
+
+// BRIS race type codes, checked before falling back to the classification text.
+fn class_type_from_code(race_type: &str) -> Option<(ClassType, Option<u8>)> {
+    let t = race_type.trim().to_uppercase();
+    let class = match t.as_str() {
+        "G1" => (ClassType::Graded, Some(1)),
+        "G2" => (ClassType::Graded, Some(2)),
+        "G3" => (ClassType::Graded, Some(3)),
+        "N" | "NO" => (ClassType::Stakes, None),
+        "A" => (ClassType::Allowance, None),
+        "AO" | "CO" => (ClassType::OptionalClaiming, None),
+        "R" | "T" => (ClassType::Starter, None),
+        "C" => (ClassType::Claiming, None),
+        "S" | "MO" => (ClassType::Maiden, None),
+        "M" => (ClassType::MaidenClaiming, None),
+        _ => return None,
+    };
+    Some(class)
+}
+
+fn class_type_from_text(text: &str) -> Option<(ClassType, Option<u8>)> {
+    let t = text.trim().to_lowercase();
+    let t = RE_RESTRICTION.replace(&t, "");
+    if t.is_empty() {
+        return None;
+    }
+
+    if let Some(cap) = RE_GRADE.captures(&t) {
+        return Some((ClassType::Graded, cap[1].parse().ok()));
+    }
+
+    let class = if t.starts_with("mdsp") || t.starts_with("msw") || t.starts_with("md sp") {
+        ClassType::Maiden
+    } else if t.starts_with("md") || t.starts_with("mcl") {
+        ClassType::MaidenClaiming
+    } else if t.starts_with("oc") || t.starts_with("aoc") {
+        ClassType::OptionalClaiming
+    } else if t.starts_with("clm") || t.starts_with("cl") {
+        ClassType::Claiming
+    } else if t.starts_with("str") || t.starts_with("sta") {
+        ClassType::Starter
+    } else if t.starts_with("alw") || t.starts_with("a ") {
+        ClassType::Allowance
+    } else if t.contains("stk") || t.contains("hcp") {
+        ClassType::Stakes
+    } else {
+        return None;
+    };
+    Some((class, None))
+}
+
+fn parse_price(text: &str) -> Option<u32> {
+    let text = text.to_lowercase().replace(',', "");
+    RE_PRICE.captures_iter(&text).find_map(|cap| {
+        let value: f64 = cap[1].parse().ok()?;
+        let value = if cap.get(2).is_some() { value * 1000.0 } else { value };
+        Some(value as u32).filter(|v| *v >= 1000)
+    })
+}
+
+fn parse_condition(text: &str) -> Option<String> {
+    RE_CONDITION
+        .captures(&text.to_lowercase())
+        .map(|cap| format!("N{}{}", &cap[1], cap[2].to_uppercase()))
+}
+
+fn class_level(class_type: ClassType, grade: Option<u8>, price: Option<u32>, condition: Option<&str>) -> f64 {
+    let base = match class_type {
+        ClassType::MaidenClaiming => 0.0,
+        ClassType::Claiming => 10.0,
+        ClassType::Maiden => 30.0,
+        ClassType::Starter => 32.0,
+        ClassType::Allowance => 40.0,
+        ClassType::OptionalClaiming => 45.0,
+        ClassType::Stakes => 60.0,
+        ClassType::Graded => 90.0 - 10.0 * grade.unwrap_or(3) as f64,
+    };
+
+    // Claiming tags double roughly every class step; purses scale more gently.
+    let price_level = match (class_type, price) {
+        (ClassType::MaidenClaiming | ClassType::Claiming | ClassType::Starter, Some(p)) => {
+            (10.0 * (p as f64 / 10_000.0).log2()).clamp(-10.0, 25.0)
+        }
+        (ClassType::Maiden | ClassType::Allowance | ClassType::OptionalClaiming, Some(p)) => {
+            (5.0 * (p as f64 / 50_000.0).log2()).clamp(-10.0, 10.0)
+        }
+        _ => 0.0,
+    };
+
+    // Non-winners-of-two-lifetime sits below non-winners-of-two-other-than.
+    let condition_level = match condition {
+        Some(c) => {
+            let n: f64 = c[1..2].parse().unwrap_or(1.0);
+            if c.ends_with('X') { n * 2.0 } else { n - 1.0 }
+        }
+        None => 0.0,
+    };
+
+    base + price_level + condition_level
+}
+
+pub fn parse_race_class(race_type: &str, classification: &str, claiming_price: Option<u32>, purse: Option<u32>) -> Option<RaceClass> {
+    let (class_type, grade) = class_type_from_code(race_type).or_else(|| class_type_from_text(classification))?;
+    let price = match class_type {
+        ClassType::MaidenClaiming | ClassType::Claiming | ClassType::Starter => {
+            claiming_price.filter(|p| *p > 0).or_else(|| parse_price(classification))
+        }
+        _ => parse_price(classification).or(purse.filter(|p| *p > 0)),
+    };
+    let condition = parse_condition(classification);
+    let level = class_level(class_type, grade, price, condition.as_deref());
+
+    Some(RaceClass { class_type, grade, price, condition, level })
+}
+
+pub fn todays_race_class(race: &Race) -> Option<RaceClass> {
+    parse_race_class(&race.race_type, &race.todays_race_classification, race.claiming_price, race.purse)
+}
+
+pub fn class_change(h: &Horse, race: &Race) -> Option<ClassChange> {
+    let today = todays_race_class(race)?;
+    let pp = h.past_performances.first()?;
+    let last = parse_race_class(&pp.race_type, &pp.race_classication, pp.claiming_price, pp.purse)?;
+
+    let level_change = today.level - last.level;
+    let direction = if level_change > SAME_CLASS_TOLERANCE {
+        ClassDirection::Rise
+    } else if level_change < -SAME_CLASS_TOLERANCE {
+        ClassDirection::Drop
+    } else {
+        ClassDirection::Same
+    };
+
+    let claiming_price_change = race
+        .claiming_price
+        .filter(|p| *p > 0)
+        .zip(pp.claiming_price.filter(|p| *p > 0))
+        .map(|(t, l)| t as i64 - l as i64);
+    let purse_change = race
+        .purse
+        .filter(|p| *p > 0)
+        .zip(pp.purse.filter(|p| *p > 0))
+        .map(|(t, l)| t as i64 - l as i64);
+
+    Some(ClassChange { today, last, level_change, claiming_price_change, purse_change, direction })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::PastPerformance;
+
+    fn class(classification: &str) -> RaceClass {
+        parse_race_class("", classification, None, None).unwrap()
+    }
+
+    #[test]
+    fn parses_class_price_and_condition_from_text() {
+        let alw = class("3yo Alw 62000");
+        assert_eq!((alw.class_type, alw.price), (ClassType::Allowance, Some(62000)));
+
+        let clm = class("Clm 25000N2L");
+        assert_eq!(clm.class_type, ClassType::Claiming);
+        assert_eq!(clm.price, Some(25000));
+        assert_eq!(clm.condition.as_deref(), Some("N2L"));
+
+        assert_eq!(class("OC 62.5k/N1X").price, Some(62500));
+        assert_eq!(class("Mcl $16,000").price, Some(16000));
+        assert_eq!(class("G2 Travers").grade, Some(2));
+        assert!(parse_race_class("", "", None, None).is_none());
+    }
+
+    #[test]
+    fn race_type_code_wins_over_text() {
+        let rc = parse_race_class("C", "Alw 62000", Some(20000), None).unwrap();
+        assert_eq!((rc.class_type, rc.price), (ClassType::Claiming, Some(20000)));
+    }
+
+    #[test]
+    fn claiming_level_rises_with_price() {
+        assert!(class("Clm 50000").level > class("Clm 25000").level);
+        assert!(class("Alw 62000").level > class("Clm 25000").level);
+    }
+
+    #[test]
+    fn class_change_compares_today_with_last_start() {
+        let h = Horse {
+            past_performances: vec![PastPerformance {
+                race_classication: "Clm 50000".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let race = Race {
+            todays_race_classification: "Clm 25000".to_string(),
+            claiming_price: Some(25000),
+            ..Default::default()
+        };
+
+        let change = class_change(&h, &race).unwrap();
+        assert_eq!(change.direction, ClassDirection::Drop);
+        assert!(change.level_change < 0.0);
+        assert_eq!(change.claiming_price_change, None);
+        assert!(class_change(&Horse::default(), &race).is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::models::connections::{ConnectionChangeKind, ConnectionFactor, TrainerSwitch};
+use crate::models::model_config::ConnectionsConfig;
+use crate::models::racecard::{Horse, PastPerformance, Race, Racecard};
+
+fn same_person(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+fn win_pct(starts: Option<u32>, wins: Option<u32>, min_starts: u32) -> Option<f64> {
+    match (starts, wins) {
+        (Some(s), Some(w)) if s >= min_starts && s > 0 => Some(100.0 * w as f64 / s as f64),
+        _ => None,
+    }
+}
+
+fn parse_count(raw: &str) -> Option<u32> {
+    raw.trim().parse().ok()
+}
+
+pub fn trainer_switch(pp: &PastPerformance) -> Option<TrainerSwitch> {
+    let trainer = pp.claimed_and_trainer_switches_2.trim();
+    if trainer.is_empty() {
+        return None;
+    }
+
+    Some(TrainerSwitch {
+        date: pp.claimed_and_trainer_switches_1.trim().to_string(),
+        trainer: trainer.to_string(),
+        starts: parse_count(&pp.claimed_and_trainer_switches_3),
+        wins: parse_count(&pp.claimed_and_trainer_switches_4),
+    })
+}
+
+// Current-year record first, falling back to the meet record for riders new to the circuit.
+fn jockey_win_pct(h: &Horse, min_starts: u32) -> Option<f64> {
+    win_pct(h.jockey_starts_current_year, h.jockey_wins_current_year, min_starts)
+        .or_else(|| win_pct(h.jockey_starts, h.jockey_wins, min_starts))
+}
+
+fn trainer_win_pct(h: &Horse, min_starts: u32) -> Option<f64> {
+    win_pct(h.trainer_starts_current_year, h.trainer_wins_current_year, min_starts)
+        .or_else(|| win_pct(h.trainer_starts, h.trainer_wins, min_starts))
+}
+
+// Only today's connections carry stats, so a previous rider or trainer is looked up among
+// today's starters, across the whole card when it is available.
+fn other_starter<'a>(
+    race: &'a Race,
+    racecard: Option<&'a Racecard>,
+    h: &Horse,
+    matches: impl Fn(&Horse) -> bool,
+) -> Option<&'a Horse> {
+    let races = racecard.map(|rc| rc.races.as_slice()).unwrap_or(std::slice::from_ref(race));
+    races
+        .iter()
+        .flat_map(|r| r.horses.iter().map(move |o| (r, o)))
+        .filter(|(r, o)| r.race_number != race.race_number || o.program_number != h.program_number)
+        .map(|(_, o)| o)
+        .filter(|o| !o.scratched)
+        .find(|o| matches(o))
+}
+
+fn jockey_factor(
+    h: &Horse,
+    race: &Race,
+    racecard: Option<&Racecard>,
+    config: &ConnectionsConfig,
+) -> Option<ConnectionFactor> {
+    let previous = h.past_performances.first()?.jockey.trim();
+    let current = h.todays_jockey.trim();
+    if previous.is_empty() || current.is_empty() || same_person(previous, current) {
+        return None;
+    }
+
+    let current_win_pct = jockey_win_pct(h, config.min_starts);
+    let previous_win_pct = other_starter(race, racecard, h, |o| same_person(&o.todays_jockey, previous))
+        .and_then(|o| jockey_win_pct(o, config.min_starts));
+    let win_pct_delta = current_win_pct.zip(previous_win_pct).map(|(c, p)| c - p);
+    // BRIS has no jockey-only meet ROI (the meet ROI is for the trainer/jockey combo, which
+    // scoring already uses as `trainer_jockey_roi`), so the rider's current-year ROI is used.
+    let roi = h.jockey_roi_current_year;
+
+    let kind = match win_pct_delta {
+        Some(d) if d >= config.jockey_change_threshold => ConnectionChangeKind::JockeyUpgrade,
+        Some(d) if d <= -config.jockey_change_threshold => ConnectionChangeKind::JockeyDowngrade,
+        _ => ConnectionChangeKind::JockeyChange,
+    };
+
+    let mut contribution = win_pct_delta.map(|d| config.jockey_win_pct_delta.apply(d)).unwrap_or(0.0);
+    if let Some(r) = roi {
+        contribution += config.jockey_roi.apply(r);
+    }
+
+    let explanation = match (previous_win_pct, current_win_pct) {
+        (Some(p), Some(c)) => format!("{} ({:.0}% wins) replaces {} ({:.0}% wins)", current, c, previous, p),
+        (None, Some(c)) => format!("{} ({:.0}% wins) replaces {}", current, c, previous),
+        _ => format!("{} replaces {}", current, previous),
+    };
+
+    Some(ConnectionFactor {
+        kind,
+        previous: previous.to_string(),
+        current: current.to_string(),
+        previous_win_pct,
+        current_win_pct,
+        win_pct_delta,
+        roi,
+        contribution,
+        explanation,
+    })
+}
+
+// The switch records name the trainer who took over at each change along with that trainer's
+// record, so an older switch to the previous trainer gives the previous trainer's stats.
+fn switch_win_pct(switches: &[TrainerSwitch], trainer: &str, min_starts: u32) -> Option<f64> {
+    switches
+        .iter()
+        .filter(|s| same_person(&s.trainer, trainer))
+        .find_map(|s| win_pct(s.starts, s.wins, min_starts))
+}
+
+fn trainer_factor(
+    h: &Horse,
+    race: &Race,
+    racecard: Option<&Racecard>,
+    config: &ConnectionsConfig,
+) -> Option<ConnectionFactor> {
+    let last = h.past_performances.first()?;
+    let previous = last.trainer.trim();
+    let current = h.todays_trainer.trim();
+    let switches: Vec<TrainerSwitch> = h.past_performances.iter().filter_map(trainer_switch).collect();
+
+    // A switch recorded on the last race to today's trainer is a change even when the PP
+    // trainer name is spelled differently.
+    let switched_last_race = trainer_switch(last).is_some_and(|s| same_person(&s.trainer, current));
+    if previous.is_empty() || current.is_empty() || (same_person(previous, current) && !switched_last_race) {
+        return None;
+    }
+
+    let claimed = !last.claimed_code.trim().is_empty();
+    let current_win_pct = trainer_win_pct(h, config.min_starts)
+        .or_else(|| switch_win_pct(&switches, current, config.min_starts));
+    let previous_win_pct = switch_win_pct(&switches, previous, config.min_starts).or_else(|| {
+        other_starter(race, racecard, h, |o| same_person(&o.todays_trainer, previous))
+            .and_then(|o| trainer_win_pct(o, config.min_starts))
+    });
+    let win_pct_delta = current_win_pct.zip(previous_win_pct).map(|(c, p)| c - p);
+
+    let mut contribution = win_pct_delta.map(|d| config.trainer_win_pct_delta.apply(d)).unwrap_or(0.0);
+    if claimed {
+        contribution += config.first_after_claim;
+    }
+
+    let kind = if claimed { ConnectionChangeKind::FirstAfterClaim } else { ConnectionChangeKind::TrainerChange };
+    let action = if claimed { "claimed by" } else { "moved to" };
+    let explanation = match (previous_win_pct, current_win_pct) {
+        (Some(p), Some(c)) => format!("{} {} ({:.0}% wins) from {} ({:.0}% wins)", action, current, c, previous, p),
+        (None, Some(c)) => format!("{} {} ({:.0}% wins) from {}", action, current, c, previous),
+        _ => format!("{} {} from {}", action, current, previous),
+    };
+
+    Some(ConnectionFactor {
+        kind,
+        previous: previous.to_string(),
+        current: current.to_string(),
+        previous_win_pct,
+        current_win_pct,
+        win_pct_delta,
+        roi: h.trainer_roi_current_year,
+        contribution,
+        explanation,
+    })
+}
+
+pub fn analyze_connections(
+    h: &Horse,
+    race: &Race,
+    racecard: Option<&Racecard>,
+    config: &ConnectionsConfig,
+) -> Vec<ConnectionFactor> {
+    jockey_factor(h, race, racecard, config)
+        .into_iter()
+        .chain(trainer_factor(h, race, racecard, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ConnectionsConfig {
+        ConnectionsConfig::default()
+    }
+
+    fn last_race(jockey: &str, trainer: &str) -> PastPerformance {
+        PastPerformance { jockey: jockey.to_string(), trainer: trainer.to_string(), ..Default::default() }
+    }
+
+    fn horse(program_number: &str, jockey: &str, jockey_wins: u32, trainer: &str, trainer_wins: u32) -> Horse {
+        Horse {
+            program_number: program_number.to_string(),
+            todays_jockey: jockey.to_string(),
+            jockey_starts_current_year: Some(100),
+            jockey_wins_current_year: Some(jockey_wins),
+            todays_trainer: trainer.to_string(),
+            trainer_starts_current_year: Some(100),
+            trainer_wins_current_year: Some(trainer_wins),
+            ..Default::default()
+        }
+    }
+
+    fn race(horses: Vec<Horse>) -> Race {
+        Race { race_number: Some(1), horses, ..Default::default() }
+    }
+
+    fn jockey_change(current_wins: u32, previous_wins: u32) -> ConnectionFactor {
+        let mut switched = horse("1", "New Rider", current_wins, "Same Barn", 15);
+        switched.past_performances = vec![last_race("Old Rider", "Same Barn")];
+        let race = race(vec![switched.clone(), horse("2", "Old Rider", previous_wins, "Other Barn", 15)]);
+        let factors = analyze_connections(&switched, &race, None, &config());
+        assert_eq!(factors.len(), 1);
+        factors.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn jockey_changes_are_graded_against_the_threshold() {
+        let threshold = config().jockey_change_threshold as u32;
+
+        let up = jockey_change(10 + threshold, 10);
+        assert_eq!(up.kind, ConnectionChangeKind::JockeyUpgrade);
+        assert_eq!(up.win_pct_delta, Some(threshold as f64));
+        assert!(up.contribution > 0.0);
+
+        assert_eq!(jockey_change(10, 10 + threshold).kind, ConnectionChangeKind::JockeyDowngrade);
+        assert_eq!(jockey_change(10 + threshold - 1, 10).kind, ConnectionChangeKind::JockeyChange);
+    }
+
+    #[test]
+    fn first_start_after_a_claim_adds_the_claim_bonus() {
+        let mut claimed = horse("1", "Same Rider", 15, "New Barn", 20);
+        let mut last = last_race("Same Rider", "Old Barn");
+        last.claimed_code = "c".to_string();
+        claimed.past_performances = vec![last];
+
+        let factors = analyze_connections(&claimed, &race(vec![claimed.clone()]), None, &config());
+
+        assert_eq!(factors.len(), 1);
+        assert_eq!(factors[0].kind, ConnectionChangeKind::FirstAfterClaim);
+        assert_eq!(factors[0].previous_win_pct, None);
+        assert!((factors[0].contribution - config().first_after_claim).abs() < 1e-9);
+    }
+
+    #[test]
+    fn previous_trainer_record_comes_from_switches_then_other_starters() {
+        let mut moved = horse("1", "Same Rider", 15, "New Barn", 20);
+        let mut last = last_race("Same Rider", "Old Barn");
+        last.claimed_and_trainer_switches_1 = "20250601".to_string();
+        last.claimed_and_trainer_switches_2 = "Old Barn".to_string();
+        last.claimed_and_trainer_switches_3 = "50".to_string();
+        last.claimed_and_trainer_switches_4 = "5".to_string();
+        moved.past_performances = vec![last];
+        let card = race(vec![moved.clone(), horse("2", "Other Rider", 15, "Old Barn", 30)]);
+
+        let from_switch = analyze_connections(&moved, &card, None, &config());
+        assert_eq!(from_switch[0].kind, ConnectionChangeKind::TrainerChange);
+        assert_eq!(from_switch[0].previous_win_pct, Some(10.0));
+
+        // Too few starts in the switch record, so the old barn's runner in this race stands in.
+        moved.past_performances[0].claimed_and_trainer_switches_3 = "5".to_string();
+        let from_starter = analyze_connections(&moved, &card, None, &config());
+        assert_eq!(from_starter[0].previous_win_pct, Some(30.0));
+        assert_eq!(from_starter[0].win_pct_delta, Some(-10.0));
+    }
+
+    #[test]
+    fn other_starters_come_from_the_whole_card() {
+        let mut switched = horse("1", "New Rider", 20, "Same Barn", 15);
+        switched.past_performances = vec![last_race("Old Rider", "Same Barn")];
+        let today = race(vec![switched.clone()]);
+        let later = race(vec![horse("1", "Old Rider", 10, "Barn", 15)]);
+        let later = Race { race_number: Some(5), ..later };
+        let card = Racecard { races: vec![today.clone(), later], ..Default::default() };
+
+        assert_eq!(analyze_connections(&switched, &today, None, &config())[0].previous_win_pct, None);
+        assert_eq!(analyze_connections(&switched, &today, Some(&card), &config())[0].previous_win_pct, Some(10.0));
+    }
+}
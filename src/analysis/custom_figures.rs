@@ -0,0 +1,182 @@
+use crate::models::custom_figures::{CustomFigure, FigurePar};
+use crate::models::model_config::CustomFigureConfig;
+use crate::models::racecard::{Horse, PastPerformance};
+
+pub fn find_figure_par<'a>(pars: &'a [FigurePar], pp: &PastPerformance) -> Option<&'a FigurePar> {
+    let distance = pp.distance?;
+    let track_code = pp.track_code.trim();
+    let surface = pp.surface.trim();
+
+    pars.iter().find(|p| {
+        p.distance == distance && p.surface == surface && p.track_code.eq_ignore_ascii_case(track_code)
+    })
+}
+
+// Leader fractions are the leader's time, so a trailing horse is slower by its beaten lengths.
+fn own_time(leader_time: Option<f64>, beaten_lengths: Option<f64>, config: &CustomFigureConfig) -> Option<f64> {
+    let leader_time = leader_time.filter(|t| *t > 0.0)?;
+    Some(leader_time + beaten_lengths.unwrap_or(0.0).max(0.0) * config.seconds_per_length)
+}
+
+// Sprint calls are at the first two fractions. Routes report a third fraction, and their calls fall at the
+// second and third.
+fn call_fractions(
+    fraction_1: Option<f64>,
+    fraction_2: Option<f64>,
+    fraction_3: Option<f64>,
+) -> (Option<f64>, Option<f64>) {
+    match fraction_3 {
+        Some(_) => (fraction_2, fraction_3),
+        None => (fraction_1, fraction_2),
+    }
+}
+
+fn points_per_second(distance: i32, config: &CustomFigureConfig) -> f64 {
+    let distance = (distance.abs() as f64).max(1.0);
+    config.points_per_length / config.seconds_per_length * (config.reference_distance as f64 / distance)
+}
+
+fn figure(par_time: Option<f64>, time: Option<f64>, pps: f64, config: &CustomFigureConfig) -> Option<f64> {
+    Some(config.par_figure + (par_time? - time?) * pps)
+}
+
+pub fn custom_figure(pp: &PastPerformance, pars: &[FigurePar], config: &CustomFigureConfig) -> Option<CustomFigure> {
+    let distance = pp.distance?;
+    let (first_call, second_call) = call_fractions(pp.fraction_1, pp.fraction_2, pp.fraction_3);
+    let first_call_time = own_time(first_call, pp.first_call_between_lengths, config);
+    let second_call_time = own_time(second_call, pp.second_call_between_lengths, config);
+    let final_time = own_time(pp.final_time, pp.finish_between_lengths, config);
+
+    let par = find_figure_par(pars, pp).filter(|p| p.races >= config.min_par_races);
+    let pps = points_per_second(distance, config);
+
+    let (first_call_figure, second_call_figure, late_pace_figure, speed_figure) = match par {
+        Some(par) => {
+            let (par_first_call, par_second_call) = call_fractions(par.fraction_1, par.fraction_2, par.fraction_3);
+            let late_time = final_time.zip(second_call_time).map(|(f, s)| f - s);
+            let par_late = par.final_time.zip(par_second_call).map(|(f, s)| f - s);
+            // A slower-than-usual surface (higher variant) slows every call, so credit it back against the par's
+            // average variant.
+            let variant = pp
+                .track_variant
+                .zip(par.track_variant)
+                .map(|(v, par_v)| (v as f64 - par_v) * config.variant_factor)
+                .unwrap_or(0.0);
+            let adjusted = |f: Option<f64>| f.map(|f| f + variant);
+            (
+                adjusted(figure(par_first_call, first_call_time, pps, config)),
+                adjusted(figure(par_second_call, second_call_time, pps, config)),
+                adjusted(figure(par_late, late_time, pps, config)),
+                adjusted(figure(par.final_time, final_time, pps, config)),
+            )
+        }
+        None => (None, None, None, None),
+    };
+
+    Some(CustomFigure {
+        race_date: pp.race_date.clone(),
+        track_code: pp.track_code.clone(),
+        surface: pp.surface.clone(),
+        distance,
+        first_call_time,
+        second_call_time,
+        final_time,
+        first_call_figure,
+        second_call_figure,
+        late_pace_figure,
+        speed_figure,
+    })
+}
+
+pub fn custom_figures_for_horse(horse: &Horse, pars: &[FigurePar], config: &CustomFigureConfig) -> Vec<CustomFigure> {
+    horse
+        .past_performances
+        .iter()
+        .filter_map(|pp| custom_figure(pp, pars, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn par(distance: i32, fractions: [Option<f64>; 3], final_time: f64) -> FigurePar {
+        FigurePar {
+            track_code: "SAR".to_string(),
+            surface: "D".to_string(),
+            distance,
+            races: 5,
+            fraction_1: fractions[0],
+            fraction_2: fractions[1],
+            fraction_3: fractions[2],
+            final_time: Some(final_time),
+            track_variant: Some(18.0),
+        }
+    }
+
+    fn pp(distance: i32, fractions: [Option<f64>; 3], final_time: f64) -> PastPerformance {
+        PastPerformance {
+            track_code: "sar".to_string(),
+            surface: "D".to_string(),
+            distance: Some(distance),
+            fraction_1: fractions[0],
+            fraction_2: fractions[1],
+            fraction_3: fractions[2],
+            final_time: Some(final_time),
+            track_variant: Some(18),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("figure");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    const SPRINT: [Option<f64>; 3] = [Some(22.0), Some(45.0), None];
+
+    #[test]
+    fn a_wire_to_wire_run_at_par_earns_the_par_figure() {
+        let config = CustomFigureConfig::default();
+        let figure = custom_figure(&pp(1320, SPRINT, 70.0), &[par(1320, SPRINT, 70.0)], &config).unwrap();
+
+        for f in [figure.first_call_figure, figure.second_call_figure, figure.late_pace_figure, figure.speed_figure] {
+            assert_close(f, config.par_figure);
+        }
+    }
+
+    #[test]
+    fn beaten_lengths_cost_points_at_each_call() {
+        let config = CustomFigureConfig::default();
+        let mut run = pp(1320, SPRINT, 70.0);
+        run.first_call_between_lengths = Some(2.0);
+        run.finish_between_lengths = Some(1.0);
+
+        let figure = custom_figure(&run, &[par(1320, SPRINT, 70.0)], &config).unwrap();
+
+        assert_close(figure.first_call_time, 22.4);
+        assert_close(figure.first_call_figure, config.par_figure - 2.0 * config.points_per_length);
+        assert_close(figure.speed_figure, config.par_figure - config.points_per_length);
+        assert_close(figure.second_call_figure, config.par_figure);
+    }
+
+    #[test]
+    fn routes_take_their_calls_from_the_second_and_third_fractions() {
+        let fractions = [Some(23.5), Some(47.0), Some(71.5)];
+        let figure = custom_figure(&pp(1870, fractions, 104.0), &[], &CustomFigureConfig::default()).unwrap();
+
+        assert_eq!((figure.first_call_time, figure.second_call_time), (Some(47.0), Some(71.5)));
+        assert_eq!(figure.speed_figure, None);
+    }
+
+    #[test]
+    fn a_slow_track_variant_is_credited_back() {
+        let config = CustomFigureConfig::default();
+        let mut run = pp(1320, SPRINT, 70.0);
+        run.track_variant = Some(23);
+
+        let figure = custom_figure(&run, &[par(1320, SPRINT, 70.0)], &config).unwrap();
+
+        assert_close(figure.speed_figure, config.par_figure + 5.0 * config.variant_factor);
+    }
+}
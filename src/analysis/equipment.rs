@@ -0,0 +1,111 @@
+use crate::models::equipment::EquipmentEvent;
+use crate::models::racecard::{Horse, PastPerformance};
+
+// BRIS today's medication codes: 1 Lasix, 3 Bute and Lasix, 4/5 first-time Lasix, 6/7 Lasix off.
+fn on_lasix_today(code: u32) -> bool {
+    matches!(code, 1 | 3 | 4 | 5)
+}
+
+fn ran_on_lasix(pp: &PastPerformance) -> bool {
+    pp.medication.is_some_and(|m| matches!(m, 1 | 3 | 4 | 5))
+}
+
+fn ran_in_blinkers(pp: &PastPerformance) -> bool {
+    pp.equipment.trim().to_lowercase().contains('b')
+}
+
+pub fn decode_equipment_events(h: &Horse) -> Vec<EquipmentEvent> {
+    let mut events = Vec::new();
+
+    match h.equipment_change {
+        Some(1) if !h.past_performances.iter().any(ran_in_blinkers) => events.push(EquipmentEvent::FirstTimeBlinkers),
+        Some(1) => events.push(EquipmentEvent::BlinkersOn),
+        Some(2) => events.push(EquipmentEvent::BlinkersOff),
+        _ => {}
+    }
+
+    let medication = h.todays_medication_new.or(h.todays_medication_old);
+    match medication {
+        Some(4) | Some(5) => events.push(EquipmentEvent::FirstTimeLasix),
+        Some(6) | Some(7) => events.push(EquipmentEvent::LasixOff),
+        // Older cards don't flag first-time Lasix, so fall back to the PP medication history.
+        Some(code)
+            if on_lasix_today(code)
+                && !h.past_performances.is_empty()
+                && !h.past_performances.iter().any(ran_on_lasix) =>
+        {
+            events.push(EquipmentEvent::FirstTimeLasix)
+        }
+        _ => {}
+    }
+
+    match h.todays_nasal_strip_change {
+        Some(1) => events.push(EquipmentEvent::NasalStripOn),
+        Some(2) => events.push(EquipmentEvent::NasalStripOff),
+        _ => {}
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EquipmentEvent::*;
+
+    fn pp(equipment: &str, medication: Option<u32>) -> PastPerformance {
+        PastPerformance { equipment: equipment.to_string(), medication, ..Default::default() }
+    }
+
+    #[test]
+    fn blinkers_on_is_first_time_only_without_blinkers_history() {
+        let first = Horse { equipment_change: Some(1), past_performances: vec![pp("", None)], ..Default::default() };
+        assert_eq!(decode_equipment_events(&first), vec![FirstTimeBlinkers]);
+
+        let again = Horse {
+            equipment_change: Some(1),
+            past_performances: vec![pp("", None), pp("b", None)],
+            ..Default::default()
+        };
+        assert_eq!(decode_equipment_events(&again), vec![BlinkersOn]);
+
+        let off = Horse { equipment_change: Some(2), ..Default::default() };
+        assert_eq!(decode_equipment_events(&off), vec![BlinkersOff]);
+    }
+
+    #[test]
+    fn decodes_medication_codes() {
+        let first = Horse { todays_medication_new: Some(4), ..Default::default() };
+        assert_eq!(decode_equipment_events(&first), vec![FirstTimeLasix]);
+
+        let off = Horse { todays_medication_new: Some(6), ..Default::default() };
+        assert_eq!(decode_equipment_events(&off), vec![LasixOff]);
+
+        let repeat = Horse {
+            todays_medication_new: Some(1),
+            past_performances: vec![pp("", Some(1))],
+            ..Default::default()
+        };
+        assert!(decode_equipment_events(&repeat).is_empty());
+    }
+
+    #[test]
+    fn old_cards_fall_back_to_pp_medication_history() {
+        let h = Horse { todays_medication_old: Some(1), past_performances: vec![pp("", None)], ..Default::default() };
+        assert_eq!(decode_equipment_events(&h), vec![FirstTimeLasix]);
+
+        let debut = Horse { todays_medication_old: Some(1), ..Default::default() };
+        assert!(decode_equipment_events(&debut).is_empty());
+    }
+
+    #[test]
+    fn decodes_nasal_strip_alongside_other_changes() {
+        let h = Horse {
+            equipment_change: Some(2),
+            todays_medication_new: Some(5),
+            todays_nasal_strip_change: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(decode_equipment_events(&h), vec![BlinkersOff, FirstTimeLasix, NasalStripOn]);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::contextual_speed_and_pace_model::{Confidence, RaceRankResult, classify_race};
+use crate::models::exotics::{ExoticBetType, ExoticOptions, ExoticPlan, ExoticTicket, TicketStructure};
+use crate::models::model_config::ModelConfig;
+
+fn count_from(legs: &[Vec<String>], used: &mut Vec<String>) -> u32 {
+    let Some((leg, rest)) = legs.split_first() else {
+        return 1;
+    };
+
+    let mut count = 0;
+    for horse in leg {
+        if used.contains(horse) {
+            continue;
+        }
+        used.push(horse.clone());
+        count += count_from(rest, used);
+        used.pop();
+    }
+    count
+}
+
+pub fn count_combinations(legs: &[Vec<String>]) -> u32 {
+    count_from(legs, &mut Vec::new())
+}
+
+pub fn exotic_ticket(
+    bet_type: ExoticBetType,
+    structure: TicketStructure,
+    legs: Vec<Vec<String>>,
+    base_amount: f64,
+) -> ExoticTicket {
+    let combinations = count_combinations(&legs);
+
+    ExoticTicket {
+        bet_type,
+        structure,
+        legs,
+        base_amount,
+        combinations,
+        cost: combinations as f64 * base_amount,
+    }
+}
+
+fn ranked_runners(race: &RaceRankResult) -> Vec<String> {
+    let mut runners: Vec<(String, f64)> = race
+        .horses
+        .iter()
+        .filter(|h| h.win_probability.is_some())
+        .filter_map(|h| h.score.map(|s| (h.program_number.clone(), s)))
+        .collect();
+    runners.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    runners.into_iter().map(|(p, _)| p).collect()
+}
+
+fn top(runners: &[String], from: usize, to: usize) -> Vec<String> {
+    runners.iter().skip(from).take(to.saturating_sub(from)).cloned().collect()
+}
+
+// Leg widths per finishing position, as (from, to) slices of the ranked runners.
+fn ticket_shape(confidence: Confidence, bet_type: ExoticBetType) -> Option<(TicketStructure, Vec<(usize, usize)>)> {
+    use ExoticBetType::*;
+
+    let shape = match (confidence, bet_type) {
+        (Confidence::StrongSingle, Exacta) => (TicketStructure::Key, vec![(0, 1), (1, 4)]),
+        (Confidence::StrongSingle, Trifecta) => (TicketStructure::Key, vec![(0, 1), (1, 5), (1, 5)]),
+        (Confidence::StrongSingle, Superfecta) => (TicketStructure::Key, vec![(0, 1), (1, 5), (1, 5), (1, 6)]),
+        (Confidence::Playable, Exacta) => (TicketStructure::PartWheel, vec![(0, 2), (0, 4)]),
+        (Confidence::Playable, Trifecta) => (TicketStructure::PartWheel, vec![(0, 2), (0, 4), (0, 5)]),
+        (Confidence::Playable, Superfecta) => (TicketStructure::PartWheel, vec![(0, 2), (0, 4), (0, 5), (0, 6)]),
+        (Confidence::Competitive, Exacta) => (TicketStructure::Box, vec![(0, 4), (0, 4)]),
+        (Confidence::Competitive, Trifecta) => (TicketStructure::PartWheel, vec![(0, 3), (0, 4), (0, 5)]),
+        (Confidence::Competitive, Superfecta) => (TicketStructure::PartWheel, vec![(0, 3), (0, 4), (0, 5), (0, 6)]),
+        (Confidence::WideOpen, Exacta) => (TicketStructure::Box, vec![(0, 5), (0, 5)]),
+        (Confidence::WideOpen, Trifecta) => (TicketStructure::Box, vec![(0, 5), (0, 5), (0, 5)]),
+        (Confidence::WideOpen, Superfecta) => (TicketStructure::Box, vec![(0, 6), (0, 6), (0, 6), (0, 6)]),
+        (Confidence::Unscorable, _) => return None,
+    };
+
+    Some(shape)
+}
+
+fn trim_to_budget(mut ticket: ExoticTicket, budget: f64) -> Option<ExoticTicket> {
+    while ticket.cost > budget + 1e-9 {
+        if ticket.structure == TicketStructure::Box {
+            for leg in &mut ticket.legs {
+                leg.pop();
+            }
+        } else {
+            let widest = ticket
+                .legs
+                .iter()
+                .enumerate()
+                .filter(|(_, leg)| leg.len() > 1)
+                .max_by_key(|(i, leg)| (leg.len(), *i))
+                .map(|(i, _)| i)?;
+            ticket.legs[widest].pop();
+        }
+
+        ticket = exotic_ticket(ticket.bet_type, ticket.structure, ticket.legs, ticket.base_amount);
+        if ticket.combinations == 0 {
+            return None;
+        }
+    }
+
+    Some(ticket)
+}
+
+pub fn build_exotic_plan(race: &RaceRankResult, config: &ModelConfig, options: &ExoticOptions) -> ExoticPlan {
+    let mut runners_only = race.clone();
+    runners_only.horses.retain(|h| h.win_probability.is_some());
+    let confidence = classify_race(&runners_only, config);
+    let runners = ranked_runners(race);
+
+    let mut tickets = Vec::new();
+    let mut remaining = options.budget;
+
+    for bet_type in &options.bet_types {
+        if runners.len() < bet_type.positions() {
+            continue;
+        }
+        let Some((structure, widths)) = ticket_shape(confidence, *bet_type) else {
+            continue;
+        };
+
+        let legs = widths.iter().map(|(from, to)| top(&runners, *from, *to)).collect();
+        let ticket = exotic_ticket(*bet_type, structure, legs, options.base_amount);
+        if ticket.combinations == 0 {
+            continue;
+        }
+
+        if let Some(ticket) = trim_to_budget(ticket, remaining) {
+            remaining -= ticket.cost;
+            tickets.push(ticket);
+        }
+    }
+
+    ExoticPlan {
+        race_number: race.race_number,
+        confidence,
+        total_cost: tickets.iter().map(|t| t.cost).sum(),
+        tickets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(horses: &[&str]) -> Vec<String> {
+        horses.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn counts_boxes_and_keys_without_repeating_a_horse() {
+        assert_eq!(count_combinations(&[leg(&["1", "2", "3"]), leg(&["1", "2", "3"])]), 6);
+        assert_eq!(count_combinations(&vec![leg(&["1", "2", "3", "4"]); 3]), 24);
+        assert_eq!(count_combinations(&[leg(&["1"]), leg(&["2", "3", "4"])]), 3);
+        assert_eq!(count_combinations(&[leg(&["1"]), leg(&["1"])]), 0);
+        assert_eq!(count_combinations(&[]), 1);
+    }
+
+    #[test]
+    fn ticket_cost_is_combinations_times_base() {
+        let ticket = exotic_ticket(
+            ExoticBetType::Trifecta,
+            TicketStructure::Key,
+            vec![leg(&["1"]), leg(&["2", "3"]), leg(&["2", "3", "4"])],
+            0.5,
+        );
+        assert_eq!(ticket.combinations, 4);
+        assert_eq!(ticket.cost, 2.0);
+    }
+}
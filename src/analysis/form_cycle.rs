@@ -0,0 +1,144 @@
+use crate::contextual_speed_and_pace_model::WorkoutSig;
+use crate::models::form_cycle::{FormCycle, FormState};
+use crate::models::model_config::FormCycleConfig;
+use crate::models::racecard::Horse;
+
+fn form_cycle(state: FormState, adjustment: f64) -> FormCycle {
+    FormCycle {
+        state,
+        label: state.label().to_string(),
+        adjustment,
+        starts_since_layoff: None,
+        last_figure: None,
+        previous_top: None,
+        workout_backed: false,
+    }
+}
+
+pub fn analyze_form_cycle(h: &Horse, workout: &WorkoutSig, config: &FormCycleConfig) -> FormCycle {
+    let pps = &h.past_performances;
+    if pps.is_empty() {
+        return form_cycle(FormState::NoForm, 0.0);
+    }
+
+    let figures: Vec<Option<u32>> = pps.iter().take(config.lookback).map(|pp| pp.bris_speed_rating).collect();
+    let last_figure = figures.first().copied().flatten();
+    let previous_top = figures.iter().skip(1).flatten().copied().max();
+    let workout_backed = workout.recent_works >= config.min_works && workout.top_rank_works >= config.min_top_works;
+
+    // A PP's own days_since_last_race is the gap before that race, so the first PP with a long gap
+    // was the start back from the most recent layoff.
+    let returning_today = h.days_since_last_race.is_some_and(|d| d >= config.layoff_days);
+    let starts_since_layoff = if returning_today {
+        Some(0)
+    } else {
+        pps.iter()
+            .take(config.lookback)
+            .position(|pp| pp.days_since_last_race.is_some_and(|d| d >= config.layoff_days))
+            .map(|i| i as u32 + 1)
+    };
+
+    let adjustments = &config.adjustments;
+    let (state, adjustment) = if returning_today && workout_backed {
+        (FormState::WorkoutBackedReturn, adjustments.workout_backed_return)
+    } else if returning_today {
+        (FormState::FreshReturn, adjustments.fresh_return)
+    } else if let (Some(last), Some(top)) = (last_figure, previous_top)
+        && last as f64 >= top as f64 + config.bounce_margin
+    {
+        (FormState::BounceCandidate, adjustments.bounce)
+    } else if starts_since_layoff == Some(1) {
+        (FormState::SecondOffLayoff, adjustments.second_off_layoff)
+    } else if starts_since_layoff == Some(2) {
+        (FormState::ThirdOffLayoff, adjustments.third_off_layoff)
+    } else if let [Some(a), Some(b), Some(c), ..] = figures[..] {
+        if a > b && b > c {
+            (FormState::Improving, adjustments.improving)
+        } else if a < b && b < c {
+            (FormState::Declining, adjustments.declining)
+        } else {
+            (FormState::Steady, 0.0)
+        }
+    } else {
+        (FormState::Steady, 0.0)
+    };
+
+    FormCycle {
+        state,
+        label: state.label().to_string(),
+        adjustment,
+        starts_since_layoff,
+        last_figure,
+        previous_top,
+        workout_backed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::PastPerformance;
+
+    const NO_WORKS: WorkoutSig = WorkoutSig { recent_works: 0, top_rank_works: 0, score: 0.0 };
+
+    // Most recent start first, as (speed figure, days since the start before it).
+    fn horse(days_since_last_race: u32, starts: &[(u32, u32)]) -> Horse {
+        Horse {
+            days_since_last_race: Some(days_since_last_race),
+            past_performances: starts
+                .iter()
+                .map(|&(figure, days)| PastPerformance {
+                    bris_speed_rating: Some(figure),
+                    days_since_last_race: Some(days),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn state(h: &Horse) -> FormState {
+        analyze_form_cycle(h, &NO_WORKS, &FormCycleConfig::default()).state
+    }
+
+    #[test]
+    fn classifies_layoff_returns() {
+        let config = FormCycleConfig::default();
+        let h = horse(90, &[(85, 30), (84, 30)]);
+        let fresh = analyze_form_cycle(&h, &NO_WORKS, &config);
+        assert_eq!((fresh.state, fresh.starts_since_layoff), (FormState::FreshReturn, Some(0)));
+        assert_eq!(fresh.adjustment, config.adjustments.fresh_return);
+
+        let works = WorkoutSig { recent_works: 4, top_rank_works: 1, score: 1.0 };
+        let backed = analyze_form_cycle(&h, &works, &config);
+        assert_eq!(backed.state, FormState::WorkoutBackedReturn);
+        assert!(backed.workout_backed);
+
+        assert_eq!(state(&horse(21, &[(80, 120), (84, 30)])), FormState::SecondOffLayoff);
+        assert_eq!(state(&horse(21, &[(80, 28), (81, 120), (84, 30)])), FormState::ThirdOffLayoff);
+    }
+
+    #[test]
+    fn flags_a_new_top_as_a_bounce_candidate() {
+        let h = horse(21, &[(95, 28), (85, 28), (86, 28)]);
+        let cycle = analyze_form_cycle(&h, &NO_WORKS, &FormCycleConfig::default());
+        assert_eq!(cycle.state, FormState::BounceCandidate);
+        assert_eq!((cycle.last_figure, cycle.previous_top), (Some(95), Some(86)));
+    }
+
+    #[test]
+    fn reads_the_trend_of_the_last_three_figures() {
+        assert_eq!(state(&horse(21, &[(88, 28), (86, 28), (84, 28)])), FormState::Improving);
+        assert_eq!(state(&horse(21, &[(80, 28), (84, 28), (88, 28)])), FormState::Declining);
+        assert_eq!(state(&horse(21, &[(84, 28), (86, 28), (84, 28)])), FormState::Steady);
+        assert_eq!(state(&Horse::default()), FormState::NoForm);
+    }
+
+    #[test]
+    fn zero_lookback_does_not_panic() {
+        let config = FormCycleConfig { lookback: 0, ..Default::default() };
+        let cycle = analyze_form_cycle(&horse(21, &[(88, 28), (86, 28)]), &NO_WORKS, &config);
+        assert_eq!(cycle.state, FormState::Steady);
+        assert_eq!(cycle.last_figure, None);
+    }
+}
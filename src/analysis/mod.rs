@@ -1 +1,15 @@
-pub mod trip_handicapping_horse;
\ No newline at end of file
+pub mod trip_handicapping_horse;
+pub mod backtest;
+pub mod parameter_search;
+pub mod win_probability;
+pub mod exotics;
+pub mod pick_n;
+pub mod staking;
+pub mod pace_projection;
+pub mod custom_figures;
+pub mod class_analysis;
+pub mod trainer_angles;
+pub mod equipment;
+pub mod form_cycle;
+pub mod pedigree_switch;
+pub mod connections;
\ No newline at end of file
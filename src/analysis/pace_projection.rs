@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use crate::contextual_speed_and_pace_model::{HorseRank, RankContext, RunStyle, rank_race_with_context};
+use crate::models::model_config::{ModelConfig, PaceProjectionConfig, RunStyleOffsets};
+use crate::models::pace_projection::{
+    CallPosition, PaceProjection, PaceProjectionHorse, SpeedMapGroup, SpeedMapPosition,
+};
+use crate::models::racecard::Race;
+
+struct PaceRatings {
+    first_call: f64,
+    second_call: f64,
+    stretch: f64,
+}
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0.0), |(s, n), v| (s + v, n + 1.0));
+    if n > 0.0 { Some(sum / n) } else { None }
+}
+
+fn run_style_adjustment(run_style: RunStyle, offsets: &RunStyleOffsets) -> f64 {
+    match run_style {
+        RunStyle::E => offsets.e,
+        RunStyle::EP => offsets.ep,
+        RunStyle::P => offsets.p,
+        RunStyle::S => offsets.s,
+        RunStyle::Unk => 0.0,
+    }
+}
+
+fn post_adjustment(post: Option<u32>, distance_f: f64, config: &PaceProjectionConfig) -> f64 {
+    let per_post = if distance_f <= config.sprint_max_furlongs {
+        config.sprint_post_penalty
+    } else {
+        config.route_post_penalty
+    };
+    post.map(|p| -(p.saturating_sub(1) as f64) * per_post).unwrap_or(0.0)
+}
+
+fn call_positions(ratings: &[f64], lengths_per_point: f64) -> Vec<CallPosition> {
+    let mut order: Vec<usize> = (0..ratings.len()).collect();
+    order.sort_by(|&a, &b| ratings[b].partial_cmp(&ratings[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let leader = order.first().map(|&i| ratings[i]).unwrap_or(0.0);
+    let mut positions: Vec<CallPosition> = ratings
+        .iter()
+        .map(|_| CallPosition { position: 0, beaten_lengths: 0.0 })
+        .collect();
+
+    for (rank, &i) in order.iter().enumerate() {
+        positions[i] = CallPosition {
+            position: rank as u32 + 1,
+            beaten_lengths: ((leader - ratings[i]) * lengths_per_point).max(0.0),
+        };
+    }
+
+    positions
+}
+
+fn speed_map_position(first_call: &CallPosition, config: &PaceProjectionConfig) -> SpeedMapPosition {
+    if first_call.beaten_lengths <= config.front_lengths {
+        SpeedMapPosition::Front
+    } else if first_call.beaten_lengths <= config.stalking_lengths {
+        SpeedMapPosition::Stalking
+    } else if first_call.beaten_lengths <= config.midpack_lengths {
+        SpeedMapPosition::Midpack
+    } else {
+        SpeedMapPosition::Rear
+    }
+}
+
+fn pace_ratings(runners: &[&HorseRank], distance_f: f64, config: &PaceProjectionConfig) -> Vec<PaceRatings> {
+    let avg_early = average(runners.iter().filter_map(|h| h.rep.rep_early)).unwrap_or(0.0);
+    let avg_speed = average(runners.iter().filter_map(|h| h.rep.rep_speed)).unwrap_or(0.0);
+    let avg_late = average(runners.iter().filter_map(|h| h.rep.rep_late)).unwrap_or(0.0);
+
+    let first: Vec<f64> = runners
+        .iter()
+        .map(|h| {
+            // Quirin points either stand in for a missing early figure or add to a real one, never both.
+            let early = match (h.rep.rep_early, h.quirin) {
+                (Some(early), q) => early + q.unwrap_or(0) as f64 * config.quirin_bonus,
+                (None, Some(q)) => avg_early + (q as f64 - config.quirin_center) * config.quirin_early_points,
+                (None, None) => avg_early - config.missing_early_penalty,
+            };
+            early
+                + run_style_adjustment(h.run_style, &config.run_style_offsets)
+                + post_adjustment(h.post_position, distance_f, config)
+        })
+        .collect();
+
+    let leader = first.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // Horses within `duel_lengths` of the lead at the first call are the ones contesting the pace.
+    let in_duel = |rating: f64| (leader - rating) * config.first_call_lengths_per_point <= config.duel_lengths;
+    let contesting = first.iter().filter(|r| in_duel(**r)).count();
+    let duel_penalty = match contesting {
+        0 | 1 => 0.0,
+        2 => config.two_horse_duel_penalty,
+        _ => config.multi_horse_duel_penalty,
+    };
+
+    runners
+        .iter()
+        .zip(first.iter())
+        .map(|(h, &first_call)| {
+            let speed = h.rep.rep_speed.unwrap_or(avg_speed);
+            let late = h.rep.rep_late.unwrap_or(avg_late);
+            let pressure = if in_duel(first_call) { duel_penalty } else { 0.0 };
+
+            PaceRatings {
+                first_call,
+                second_call: first_call * config.second_call_early_weight + speed * config.second_call_speed_weight
+                    - pressure * config.second_call_pressure_weight,
+                stretch: first_call * config.stretch_early_weight
+                    + (speed + late) / 2.0 * config.stretch_finish_weight
+                    - pressure * config.stretch_pressure_weight,
+            }
+        })
+        .collect()
+}
+
+pub fn project_pace(race: &Race, ctx: &RankContext, config: &ModelConfig) -> PaceProjection {
+    let ranked = rank_race_with_context(race, ctx, config);
+    let scratched: HashSet<&str> = race
+        .horses
+        .iter()
+        .filter(|h| h.scratched)
+        .map(|h| h.program_number.as_str())
+        .collect();
+
+    let mut runners: Vec<&HorseRank> = ranked
+        .horses
+        .iter()
+        .filter(|h| !scratched.contains(h.program_number.as_str()))
+        .collect();
+    runners.sort_by_key(|h| h.post_position.unwrap_or(u32::MAX));
+
+    let pace = &config.pace_projection;
+    let ratings = pace_ratings(&runners, ranked.distance_f, pace);
+    let first_calls = call_positions(&ratings.iter().map(|r| r.first_call).collect::<Vec<f64>>(), pace.first_call_lengths_per_point);
+    let second_calls = call_positions(&ratings.iter().map(|r| r.second_call).collect::<Vec<f64>>(), pace.second_call_lengths_per_point);
+    let stretches = call_positions(&ratings.iter().map(|r| r.stretch).collect::<Vec<f64>>(), pace.stretch_lengths_per_point);
+
+    let mut horses: Vec<PaceProjectionHorse> = runners
+        .iter()
+        .zip(first_calls.into_iter().zip(second_calls).zip(stretches))
+        .map(|(h, ((first_call, second_call), stretch))| PaceProjectionHorse {
+            program_number: h.program_number.clone(),
+            horse_name: h.horse_name.clone(),
+            post_position: h.post_position,
+            run_style: h.run_style,
+            quirin: h.quirin,
+            speed_map_position: speed_map_position(&first_call, pace),
+            first_call,
+            second_call,
+            stretch,
+        })
+        .collect();
+    horses.sort_by_key(|h| h.first_call.position);
+
+    let early_speed_map = [
+        SpeedMapPosition::Front,
+        SpeedMapPosition::Stalking,
+        SpeedMapPosition::Midpack,
+        SpeedMapPosition::Rear,
+    ]
+    .into_iter()
+    .map(|position| SpeedMapGroup {
+        position,
+        program_numbers: horses
+            .iter()
+            .filter(|h| h.speed_map_position == position)
+            .map(|h| h.program_number.clone())
+            .collect(),
+    })
+    .collect();
+
+    PaceProjection {
+        race_number: ranked.race_number,
+        surface_mode: ranked.surface_mode,
+        distance_f: ranked.distance_f,
+        shape: ranked.shape,
+        horses,
+        early_speed_map,
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::NaiveDate;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use crate::analysis::backtest::run_backtest;
+use crate::models::backtest::{BacktestRace, BacktestStats};
+use crate::models::model_config::ModelConfig;
+use crate::models::parameter_search::{
+    ParameterSearchOptions, ParameterSearchReport, ParameterValue, SearchObjective, SearchStrategy, SearchTrial,
+};
+
+struct Evaluation {
+    params: Vec<ParameterValue>,
+    config: ModelConfig,
+    train_score: Option<f64>,
+    train: BacktestStats,
+}
+
+pub fn apply_parameters(base: &ModelConfig, params: &[ParameterValue]) -> Result<ModelConfig, String> {
+    let mut value = serde_json::to_value(base).map_err(|e| format!("Failed to serialize model config: {}", e))?;
+
+    for p in params {
+        let slot = value
+            .pointer_mut(&p.path)
+            .ok_or_else(|| format!("Unknown model config parameter: {}", p.path))?;
+
+        // Integer slots get an integer; out-of-range values (e.g. negative for an
+        // unsigned field) are rejected when the config is deserialized below.
+        *slot = match slot {
+            Value::Number(n) if n.is_u64() || n.is_i64() => Value::from(p.value.round() as i64),
+            Value::Number(_) => Value::from(p.value),
+            _ => return Err(format!("Model config parameter {} is not numeric", p.path)),
+        };
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid model config after applying parameters: {}", e))
+}
+
+fn current_parameters(config: &ModelConfig, options: &ParameterSearchOptions) -> Result<Vec<ParameterValue>, String> {
+    let value = serde_json::to_value(config).map_err(|e| format!("Failed to serialize model config: {}", e))?;
+
+    options
+        .parameters
+        .iter()
+        .map(|range| {
+            value
+                .pointer(&range.path)
+                .and_then(|v| v.as_f64())
+                .map(|v| ParameterValue { path: range.path.clone(), value: v })
+                .ok_or_else(|| format!("Unknown or non-numeric model config parameter: {}", range.path))
+        })
+        .collect()
+}
+
+pub fn split_by_date(
+    races: &[BacktestRace],
+    validation_from_date: &str,
+) -> Result<(Vec<BacktestRace>, Vec<BacktestRace>), String> {
+    NaiveDate::parse_from_str(validation_from_date, "%Y%m%d")
+        .map_err(|_| format!("Invalid validation date {}, expected yyyymmdd", validation_from_date))?;
+
+    Ok(races
+        .iter()
+        .cloned()
+        .partition(|r| r.date.as_str() < validation_from_date))
+}
+
+pub fn objective_score(stats: &BacktestStats, objective: SearchObjective, min_bets: u32) -> Option<f64> {
+    let flat = match objective {
+        SearchObjective::TopPickWinPct | SearchObjective::TopPickRoi => &stats.top_pick,
+        SearchObjective::WinBetRoi => &stats.win_bet,
+    };
+
+    if flat.bets == 0 || flat.bets < min_bets {
+        return None;
+    }
+
+    match objective {
+        SearchObjective::TopPickWinPct => Some(flat.hit_rate),
+        SearchObjective::TopPickRoi | SearchObjective::WinBetRoi => Some(flat.roi),
+    }
+}
+
+fn is_better(candidate: Option<f64>, best: Option<f64>) -> bool {
+    match (candidate, best) {
+        (Some(c), Some(b)) => c > b,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn evaluate(
+    races: &[BacktestRace],
+    base: &ModelConfig,
+    params: Vec<ParameterValue>,
+    options: &ParameterSearchOptions,
+) -> Result<Evaluation, String> {
+    let config = apply_parameters(base, &params)?;
+    let train = run_backtest(races, &config, options.stake).overall;
+    let train_score = objective_score(&train, options.objective, options.min_bets);
+
+    Ok(Evaluation { params, config, train_score, train })
+}
+
+// Walks the cartesian product like an odometer, so only one candidate exists at a time.
+fn grid_candidates(options: &ParameterSearchOptions) -> impl Iterator<Item = Vec<ParameterValue>> + '_ {
+    let ranges = &options.parameters;
+    let mut indexes = vec![0usize; ranges.len()];
+    let mut done = ranges.iter().any(|r| r.values.is_empty());
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+
+        let params = ranges
+            .iter()
+            .zip(&indexes)
+            .map(|(range, &i)| ParameterValue { path: range.path.clone(), value: range.values[i] })
+            .collect();
+
+        done = true;
+        for (range, i) in ranges.iter().zip(indexes.iter_mut()).rev() {
+            *i += 1;
+            if *i < range.values.len() {
+                done = false;
+                break;
+            }
+            *i = 0;
+        }
+
+        Some(params)
+    })
+}
+
+fn random_candidates(
+    options: &ParameterSearchOptions,
+    iterations: u32,
+    seed: u64,
+) -> impl Iterator<Item = Vec<ParameterValue>> + '_ {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..iterations)
+        .map(move |_| {
+            options
+                .parameters
+                .iter()
+                .map(|range| {
+                    let lo = range.values.iter().copied().fold(f64::INFINITY, f64::min);
+                    let hi = range.values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                    let value = if hi > lo { rng.gen_range(lo..=hi) } else { lo };
+                    ParameterValue { path: range.path.clone(), value }
+                })
+                .collect()
+        })
+}
+
+pub fn search_parameters(
+    races: &[BacktestRace],
+    base: &ModelConfig,
+    options: &ParameterSearchOptions,
+) -> Result<ParameterSearchReport, String> {
+    if options.parameters.is_empty() {
+        return Err("No parameters to search".to_string());
+    }
+    if let Some(range) = options.parameters.iter().find(|r| r.values.is_empty()) {
+        return Err(format!("No candidate values for parameter {}", range.path));
+    }
+
+    let (train_races, validation_races) = split_by_date(races, &options.validation_from_date)?;
+    if train_races.is_empty() {
+        return Err(format!("No training races before {}", options.validation_from_date));
+    }
+
+    let max_evaluations = options.max_evaluations.unwrap_or(u32::MAX);
+    let baseline = evaluate(&train_races, base, current_parameters(base, options)?, options)?;
+    let mut trials = vec![SearchTrial { params: baseline.params.clone(), train_score: baseline.train_score }];
+    let mut evaluations = 1u32;
+
+    let mut best = baseline;
+
+    match options.strategy {
+        SearchStrategy::Grid | SearchStrategy::Random { .. } => {
+            let candidates: Box<dyn Iterator<Item = Vec<ParameterValue>>> = match options.strategy {
+                SearchStrategy::Random { iterations, seed } => Box::new(random_candidates(options, iterations, seed)),
+                _ => Box::new(grid_candidates(options)),
+            };
+
+            for params in candidates {
+                if evaluations >= max_evaluations {
+                    break;
+                }
+
+                let eval = evaluate(&train_races, base, params, options)?;
+                evaluations += 1;
+                trials.push(SearchTrial { params: eval.params.clone(), train_score: eval.train_score });
+
+                if is_better(eval.train_score, best.train_score) {
+                    best = eval;
+                }
+            }
+        }
+        SearchStrategy::CoordinateDescent { max_rounds } => {
+            'rounds: for _ in 0..max_rounds {
+                let mut improved = false;
+
+                for (i, range) in options.parameters.iter().enumerate() {
+                    for v in &range.values {
+                        if evaluations >= max_evaluations {
+                            break 'rounds;
+                        }
+                        if best.params[i].value == *v {
+                            continue;
+                        }
+
+                        let mut params = best.params.clone();
+                        params[i].value = *v;
+                        let eval = evaluate(&train_races, base, params, options)?;
+                        evaluations += 1;
+                        trials.push(SearchTrial { params: eval.params.clone(), train_score: eval.train_score });
+
+                        if is_better(eval.train_score, best.train_score) {
+                            best = eval;
+                            improved = true;
+                        }
+                    }
+                }
+
+                if !improved {
+                    break;
+                }
+            }
+        }
+    }
+
+    let baseline_validation = run_backtest(&validation_races, base, options.stake).overall;
+    let best_validation = run_backtest(&validation_races, &best.config, options.stake).overall;
+
+    Ok(ParameterSearchReport {
+        strategy: options.strategy,
+        objective: options.objective,
+        train_races: train_races.len() as u32,
+        validation_races: validation_races.len() as u32,
+        evaluations,
+        baseline_train_score: trials[0].train_score,
+        baseline_validation_score: objective_score(&baseline_validation, options.objective, options.min_bets),
+        best_params: best.params,
+        best_train_score: best.train_score,
+        best_validation_score: objective_score(&best_validation, options.objective, options.min_bets),
+        best_train: best.train,
+        best_validation,
+        best_config: best.config,
+        trials,
+    })
+}
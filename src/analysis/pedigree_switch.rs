@@ -0,0 +1,191 @@
+use crate::contextual_speed_and_pace_model::{SurfaceMode, parse_pedigree_rating};
+use crate::models::model_config::PedigreeSwitchConfig;
+use crate::models::pedigree_switch::{SireStats, SwitchKind, SwitchSuitability};
+use crate::models::racecard::{Horse, Race};
+
+// A switch is first-time within the PP window; older starts still show up in the lifetime records.
+pub fn detect_switches(h: &Horse, race: &Race, mode: SurfaceMode, route_yards: i32) -> Vec<SwitchKind> {
+    let is_route = |distance: Option<i32>| distance.is_some_and(|d| d.abs() >= route_yards);
+    let pps = &h.past_performances;
+    if pps.is_empty() {
+        return Vec::new();
+    }
+
+    let mut switches = Vec::new();
+    if mode == SurfaceMode::Turf && !pps.iter().any(|pp| pp.surface.trim().eq_ignore_ascii_case("T")) {
+        switches.push(SwitchKind::FirstTurf);
+    }
+    if mode == SurfaceMode::Synthetic
+        && !pps.iter().any(|pp| pp.previous_all_weather_surface_indicator.trim().eq_ignore_ascii_case("A"))
+    {
+        switches.push(SwitchKind::FirstSynthetic);
+    }
+    if is_route(race.distance) && !pps.iter().any(|pp| is_route(pp.distance)) {
+        switches.push(SwitchKind::FirstRoute);
+    }
+    switches
+}
+
+fn sire_win_pct(sire_stats: &[SireStats], sire: &str, kind: SwitchKind, min_starts: u32) -> Option<f64> {
+    let sire = sire.trim();
+    if sire.is_empty() {
+        return None;
+    }
+    let stats = sire_stats.iter().find(|s| s.sire.eq_ignore_ascii_case(sire))?;
+    let (starts, wins) = match kind {
+        SwitchKind::FirstTurf => (stats.turf_starts, stats.turf_wins),
+        SwitchKind::FirstSynthetic => (stats.synthetic_starts, stats.synthetic_wins),
+        SwitchKind::FirstRoute => (stats.route_starts, stats.route_wins),
+    };
+    (starts >= min_starts && starts > 0).then(|| wins as f64 / starts as f64)
+}
+
+pub fn switch_suitability(
+    h: &Horse,
+    kind: SwitchKind,
+    sire_stats: &[SireStats],
+    config: &PedigreeSwitchConfig,
+) -> SwitchSuitability {
+    // There is no all-weather pedigree rating, so synthetic sits between the dirt and turf ratings.
+    // The mud rating isn't about a surface switch; the wet-track projection scores it.
+    let pedigree = match kind {
+        SwitchKind::FirstTurf => parse_pedigree_rating(&h.bris_turf_pedigree_rating),
+        SwitchKind::FirstRoute => parse_pedigree_rating(&h.bris_distance_pedigree_rating),
+        SwitchKind::FirstSynthetic => {
+            match (parse_pedigree_rating(&h.bris_dirt_pedigree_rating), parse_pedigree_rating(&h.bris_turf_pedigree_rating)) {
+                (Some(d), Some(t)) => Some((d + t) / 2.0),
+                (d, t) => d.or(t),
+            }
+        }
+    };
+
+    let (record_starts, record_wins) = match kind {
+        SwitchKind::FirstTurf => (h.lifetime_record_turf_starts, h.lifetime_record_turf_wins),
+        SwitchKind::FirstSynthetic => (h.lifetime_all_weather_starts, h.lifetime_all_weather_wins),
+        SwitchKind::FirstRoute => (h.lifetime_record_todays_distance_starts, h.lifetime_record_todays_distance_wins),
+    };
+    let record_win_pct = match (record_starts, record_wins) {
+        (Some(s), Some(w)) if s > 0 => Some(w as f64 / s as f64),
+        _ => None,
+    };
+
+    let sire_pct = sire_win_pct(sire_stats, &h.sire, kind, config.min_sire_starts);
+    let dam_sire_pct = sire_win_pct(sire_stats, &h.dams_sire, kind, config.min_sire_starts);
+
+    let mut score = 0.0;
+    if let Some(p) = pedigree {
+        score += config.pedigree.apply(p);
+    }
+    // Without enough stored starts for the sire, stud fee stands in as a rough measure of sire quality.
+    let stud_fee = h.sire_stud_fee.filter(|f| *f > 0);
+    match (sire_pct, stud_fee) {
+        (Some(p), _) => score += config.sire_win_pct.apply(p),
+        (None, Some(fee)) => score += config.log_stud_fee.apply((fee as f64).ln()),
+        (None, None) => {}
+    }
+    if let Some(p) = dam_sire_pct {
+        score += config.dam_sire_weight * config.sire_win_pct.apply(p);
+    }
+    if let Some(p) = record_win_pct {
+        score += config.record_win_pct.apply(p);
+    }
+
+    SwitchSuitability {
+        kind,
+        pedigree_rating: pedigree,
+        sire_win_pct: sire_pct,
+        dam_sire_win_pct: dam_sire_pct,
+        sire_stud_fee: stud_fee,
+        record_starts,
+        record_win_pct,
+        score,
+    }
+}
+
+pub fn analyze_switches(
+    h: &Horse,
+    race: &Race,
+    mode: SurfaceMode,
+    sire_stats: &[SireStats],
+    config: &PedigreeSwitchConfig,
+) -> Vec<SwitchSuitability> {
+    detect_switches(h, race, mode, config.route_yards)
+        .into_iter()
+        .map(|kind| switch_suitability(h, kind, sire_stats, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::PastPerformance;
+
+    fn pp(surface: &str, distance: i32) -> PastPerformance {
+        PastPerformance { surface: surface.to_string(), distance: Some(distance), ..Default::default() }
+    }
+
+    fn sire(name: &str, route_starts: u32, route_wins: u32) -> SireStats {
+        SireStats {
+            sire: name.to_string(),
+            offspring: 10,
+            turf_starts: 0,
+            turf_wins: 0,
+            synthetic_starts: 0,
+            synthetic_wins: 0,
+            route_starts,
+            route_wins,
+        }
+    }
+
+    #[test]
+    fn detects_first_turf_and_first_route_within_the_pp_window() {
+        let horse = Horse { past_performances: vec![pp("D", 1320), pp("D", 1430)], ..Default::default() };
+        let turf_route = Race { surface: "T".to_string(), distance: Some(1760), ..Default::default() };
+
+        let switches = detect_switches(&horse, &turf_route, SurfaceMode::Turf, 1760);
+        assert_eq!(switches, vec![SwitchKind::FirstTurf, SwitchKind::FirstRoute]);
+
+        assert!(detect_switches(&horse, &turf_route, SurfaceMode::Dirt, 1870).is_empty());
+        assert!(detect_switches(&Horse::default(), &turf_route, SurfaceMode::Turf, 1760).is_empty());
+    }
+
+    #[test]
+    fn sire_record_needs_enough_starts_before_replacing_the_stud_fee() {
+        let config = PedigreeSwitchConfig::default();
+        let horse = Horse { sire: "bar".to_string(), sire_stud_fee: Some(5000), ..Default::default() };
+
+        let thin = switch_suitability(&horse, SwitchKind::FirstRoute, &[sire("BAR", 5, 2)], &config);
+        assert_eq!(thin.sire_win_pct, None);
+        assert!((thin.score - config.log_stud_fee.apply(5000f64.ln())).abs() < 1e-9);
+
+        let proven = switch_suitability(&horse, SwitchKind::FirstRoute, &[sire("BAR", 40, 10)], &config);
+        assert_eq!(proven.sire_win_pct, Some(0.25));
+        assert!((proven.score - config.sire_win_pct.apply(0.25)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn combines_pedigree_dam_sire_and_record() {
+        let config = PedigreeSwitchConfig::default();
+        let horse = Horse {
+            dams_sire: "Dam Sire".to_string(),
+            bris_dirt_pedigree_rating: "100".to_string(),
+            bris_turf_pedigree_rating: "120".to_string(),
+            lifetime_all_weather_starts: Some(4),
+            lifetime_all_weather_wins: Some(1),
+            ..Default::default()
+        };
+
+        let dam_sire = SireStats { synthetic_starts: 30, synthetic_wins: 6, ..sire("DAM SIRE", 30, 3) };
+
+        let s = switch_suitability(&horse, SwitchKind::FirstSynthetic, &[dam_sire], &config);
+
+        assert_eq!(s.pedigree_rating, Some(110.0));
+        assert_eq!(s.sire_win_pct, None);
+        assert_eq!(s.dam_sire_win_pct, Some(0.2));
+        assert_eq!(s.record_win_pct, Some(0.25));
+        let expected = config.pedigree.apply(110.0)
+            + config.dam_sire_weight * config.sire_win_pct.apply(0.2)
+            + config.record_win_pct.apply(0.25);
+        assert!((s.score - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::contextual_speed_and_pace_model::{Confidence, RankContext, derive_race_meta_with_context};
+use crate::models::model_config::ModelConfig;
+use crate::models::pick_n::{PickNLeg, PickNOptions, PickNTicket};
+use crate::models::racecard::{Race, Racecard};
+use crate::utils::transformers::Transformers;
+
+struct LegCandidates {
+    race_number: Option<u32>,
+    confidence: Confidence,
+    runners: Vec<(String, f64)>,
+    width: usize,
+}
+
+fn initial_width(confidence: Confidence, field_size: usize) -> usize {
+    let width = match confidence {
+        Confidence::StrongSingle => 1,
+        Confidence::Playable => 2,
+        Confidence::Competitive => 3,
+        Confidence::WideOpen => 5,
+        Confidence::Unscorable => field_size,
+    };
+    width.min(field_size).max(1)
+}
+
+fn leg_candidates(race: &Race, ctx: &RankContext, config: &ModelConfig) -> LegCandidates {
+    let meta = derive_race_meta_with_context(race, ctx, config);
+
+    let mut runners: Vec<(String, f64)> = meta
+        .race_rank_result
+        .horses
+        .iter()
+        .filter_map(|h| h.win_probability.map(|p| (h.program_number.clone(), p)))
+        .collect();
+
+    // Unscored starters split the probability the model reserved for them, so they
+    // count toward field size and can still be used.
+    let unscored: Vec<&str> = race
+        .horses
+        .iter()
+        .filter(|h| !h.scratched && !runners.iter().any(|(p, _)| *p == h.program_number))
+        .map(|h| h.program_number.as_str())
+        .collect();
+    if !runners.is_empty() && !unscored.is_empty() {
+        let reserved = (1.0 - runners.iter().map(|(_, p)| p).sum::<f64>()).max(0.0);
+        let p = reserved / unscored.len() as f64;
+        runners.extend(unscored.into_iter().map(|s| (s.to_string(), p)));
+    }
+    runners.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Without model probabilities every runner is treated as equally likely.
+    if runners.is_empty() || matches!(meta.confidence, Confidence::Unscorable) {
+        let starters: Vec<&str> = race
+            .horses
+            .iter()
+            .filter(|h| !h.scratched)
+            .map(|h| h.program_number.as_str())
+            .collect();
+        let p = 1.0 / starters.len().max(1) as f64;
+        runners = starters.into_iter().map(|s| (s.to_string(), p)).collect();
+    }
+
+    LegCandidates {
+        race_number: race.race_number,
+        confidence: meta.confidence,
+        width: initial_width(meta.confidence, runners.len()),
+        runners,
+    }
+}
+
+fn leg_coverage(leg: &LegCandidates, width: usize) -> f64 {
+    leg.runners.iter().take(width).map(|(_, p)| p).sum()
+}
+
+fn combinations(legs: &[LegCandidates]) -> u32 {
+    legs.iter().map(|l| l.width as u32).product()
+}
+
+pub fn build_pick_n(
+    racecard: &Racecard,
+    start_race: u32,
+    options: &PickNOptions,
+    config: &ModelConfig,
+) -> Result<PickNTicket, String> {
+    if !(3..=6).contains(&options.legs) {
+        return Err(format!("Pick {} is not supported; use 3 to 6 legs", options.legs));
+    }
+
+    let racecard_date = Transformers::yyyymmdd_to_mmddyyyy(&racecard.date);
+    let ctx = RankContext {
+        racecard_date: racecard_date.as_deref(),
+        track_code: Some(&racecard.track_code),
+        racecard: Some(racecard),
+        ..RankContext::default()
+    };
+    let mut legs = Vec::with_capacity(options.legs as usize);
+
+    for race_number in start_race..start_race + options.legs {
+        let race = racecard
+            .races
+            .iter()
+            .find(|r| r.race_number == Some(race_number))
+            .ok_or_else(|| format!("Race {} is not on the card", race_number))?;
+
+        let leg = leg_candidates(race, &ctx, config);
+        if leg.runners.is_empty() {
+            return Err(format!("Race {} has no starters", race_number));
+        }
+        legs.push(leg);
+    }
+
+    // Narrow the leg that gives up the least coverage until the ticket fits the budget.
+    while combinations(&legs) as f64 * options.base_amount > options.budget + 1e-9 {
+        let narrowest_loss = legs
+            .iter()
+            .enumerate()
+            .filter(|(_, leg)| leg.width > 1)
+            .map(|(i, leg)| {
+                let before = leg_coverage(leg, leg.width);
+                let after = leg_coverage(leg, leg.width - 1);
+                (i, if before > 0.0 { after / before } else { 0.0 })
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        match narrowest_loss {
+            Some((i, _)) => legs[i].width -= 1,
+            None => {
+                return Err(format!(
+                    "An all-singles Pick {} costs {:.2}, over the {:.2} budget",
+                    options.legs, options.base_amount, options.budget
+                ));
+            }
+        }
+    }
+
+    let combinations = combinations(&legs);
+    let legs: Vec<PickNLeg> = legs
+        .iter()
+        .map(|leg| PickNLeg {
+            race_number: leg.race_number,
+            confidence: leg.confidence,
+            field_size: leg.runners.len() as u32,
+            selections: leg.runners.iter().take(leg.width).map(|(p, _)| p.clone()).collect(),
+            coverage: leg_coverage(leg, leg.width),
+        })
+        .collect();
+
+    Ok(PickNTicket {
+        pool: format!("Pick {}", options.legs),
+        start_race,
+        coverage_probability: legs.iter().map(|l| l.coverage).product(),
+        combinations,
+        base_amount: options.base_amount,
+        cost: combinations as f64 * options.base_amount,
+        legs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::Horse;
+
+    // Horses without past performances are unscorable, so every leg starts at full field width.
+    fn card(field_sizes: &[usize]) -> Racecard {
+        let races = field_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| Race {
+                id: i as i64 + 1,
+                race_number: Some(i as u32 + 1),
+                horses: (1..=size)
+                    .map(|p| Horse { id: p as i64, program_number: p.to_string(), ..Default::default() })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect();
+        Racecard { track_code: "SAR".to_string(), date: "20260101".to_string(), races, ..Default::default() }
+    }
+
+    fn options(legs: u32, budget: f64) -> PickNOptions {
+        PickNOptions { legs, base_amount: 0.5, budget }
+    }
+
+    #[test]
+    fn combinations_multiply_the_leg_widths() {
+        let ticket = build_pick_n(&card(&[4, 3, 2]), 1, &options(3, 100.0), &ModelConfig::default()).unwrap();
+
+        assert_eq!(ticket.legs.iter().map(|l| l.selections.len()).collect::<Vec<_>>(), vec![4, 3, 2]);
+        assert_eq!(ticket.combinations, 24);
+        assert!((ticket.cost - 12.0).abs() < 1e-9);
+        assert!((ticket.coverage_probability - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn prunes_legs_until_the_ticket_fits_the_budget() {
+        let ticket = build_pick_n(&card(&[4, 4, 4]), 1, &options(3, 10.0), &ModelConfig::default()).unwrap();
+
+        let product: usize = ticket.legs.iter().map(|l| l.selections.len()).product();
+        assert_eq!(product as u32, ticket.combinations);
+        assert!(ticket.cost <= 10.0);
+        // Level fields lose the same coverage per cut, so the pruning is spread across the legs.
+        assert!(ticket.legs.iter().all(|l| l.selections.len() < 4));
+        assert!(ticket.coverage_probability < 1.0);
+    }
+
+    #[test]
+    fn rejects_a_budget_below_all_singles() {
+        let err = build_pick_n(&card(&[4, 4, 4]), 1, &options(3, 0.25), &ModelConfig::default()).unwrap_err();
+
+        assert!(err.contains("all-singles Pick 3"));
+    }
+
+    #[test]
+    fn rejects_unsupported_leg_counts_and_missing_races() {
+        assert!(build_pick_n(&card(&[4, 4]), 1, &options(2, 100.0), &ModelConfig::default()).is_err());
+        let err = build_pick_n(&card(&[4, 4, 4]), 2, &options(3, 100.0), &ModelConfig::default()).unwrap_err();
+        assert_eq!(err, "Race 4 is not on the card");
+    }
+}
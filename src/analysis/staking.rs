@@ -0,0 +1,130 @@
+use crate::models::staking::{BankrollSimulation, BankrollStep, StakedBet, StakingStrategy};
+
+pub fn kelly_fraction(probability: f64, odds: f64) -> f64 {
+    if odds <= 0.0 || !(0.0..=1.0).contains(&probability) {
+        return 0.0;
+    }
+    (probability - (1.0 - probability) / odds).max(0.0)
+}
+
+pub fn stake_for_bet(strategy: StakingStrategy, bankroll: f64, probability: f64, odds: f64) -> f64 {
+    if bankroll <= 0.0 {
+        return 0.0;
+    }
+
+    let stake = match strategy {
+        StakingStrategy::Flat { amount } => amount,
+        StakingStrategy::PercentOfBankroll { fraction } => bankroll * fraction,
+        StakingStrategy::Kelly { multiplier, max_fraction } => {
+            let fraction = kelly_fraction(probability, odds) * multiplier;
+            bankroll * max_fraction.map(|m| fraction.min(m)).unwrap_or(fraction)
+        }
+    };
+
+    stake.max(0.0).min(bankroll)
+}
+
+pub fn simulate_bankroll(starting_bankroll: f64, strategy: StakingStrategy, bets: &[StakedBet]) -> BankrollSimulation {
+    let mut bankroll = starting_bankroll;
+    let mut peak = starting_bankroll;
+    let mut max_drawdown: f64 = 0.0;
+    let mut max_drawdown_amount: f64 = 0.0;
+    let mut bets_placed = 0;
+    let mut wins = 0;
+    let mut total_staked = 0.0;
+    let mut steps = Vec::with_capacity(bets.len());
+
+    for bet in bets {
+        let stake = stake_for_bet(strategy, bankroll, bet.probability, bet.odds);
+        if stake > 0.0 {
+            bets_placed += 1;
+            total_staked += stake;
+            if bet.won {
+                wins += 1;
+                bankroll += stake * bet.odds;
+            } else {
+                bankroll -= stake;
+            }
+        }
+
+        peak = peak.max(bankroll);
+        let drawdown = if peak > 0.0 { (peak - bankroll) / peak } else { 0.0 };
+        max_drawdown = max_drawdown.max(drawdown);
+        max_drawdown_amount = max_drawdown_amount.max(peak - bankroll);
+
+        steps.push(BankrollStep { stake, won: bet.won, bankroll, drawdown });
+    }
+
+    BankrollSimulation {
+        strategy,
+        starting_bankroll,
+        ending_bankroll: bankroll,
+        peak_bankroll: peak,
+        growth: if starting_bankroll > 0.0 { bankroll / starting_bankroll - 1.0 } else { 0.0 },
+        max_drawdown,
+        max_drawdown_amount,
+        bets_placed,
+        wins,
+        total_staked,
+        busted: bankroll <= 0.0,
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn kelly_fraction_is_edge_over_odds() {
+        assert!(close(kelly_fraction(0.5, 2.0), 0.25));
+        assert!(close(kelly_fraction(0.25, 3.0), 0.0));
+        assert_eq!(kelly_fraction(0.1, 2.0), 0.0);
+        assert_eq!(kelly_fraction(1.5, 2.0), 0.0);
+        assert_eq!(kelly_fraction(0.5, 0.0), 0.0);
+    }
+
+    #[test]
+    fn stakes_are_scaled_capped_and_bounded_by_bankroll() {
+        let half_kelly = StakingStrategy::Kelly { multiplier: 0.5, max_fraction: None };
+        assert!(close(stake_for_bet(half_kelly, 1000.0, 0.5, 2.0), 125.0));
+
+        let capped = StakingStrategy::Kelly { multiplier: 1.0, max_fraction: Some(0.1) };
+        assert!(close(stake_for_bet(capped, 1000.0, 0.5, 2.0), 100.0));
+
+        assert!(close(stake_for_bet(StakingStrategy::PercentOfBankroll { fraction: 0.02 }, 500.0, 0.0, 0.0), 10.0));
+        assert!(close(stake_for_bet(StakingStrategy::Flat { amount: 50.0 }, 20.0, 0.0, 0.0), 20.0));
+        assert_eq!(stake_for_bet(StakingStrategy::Flat { amount: 50.0 }, 0.0, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn simulation_tracks_bankroll_peak_and_drawdown() {
+        let bets = [
+            StakedBet { probability: 0.3, odds: 3.0, won: true },
+            StakedBet { probability: 0.3, odds: 3.0, won: false },
+            StakedBet { probability: 0.3, odds: 3.0, won: false },
+        ];
+        let sim = simulate_bankroll(100.0, StakingStrategy::Flat { amount: 10.0 }, &bets);
+
+        assert!(close(sim.ending_bankroll, 110.0));
+        assert!(close(sim.peak_bankroll, 130.0));
+        assert!(close(sim.max_drawdown_amount, 20.0));
+        assert!(close(sim.max_drawdown, 20.0 / 130.0));
+        assert!(close(sim.growth, 0.1));
+        assert_eq!((sim.bets_placed, sim.wins), (3, 1));
+        assert!(!sim.busted);
+    }
+
+    #[test]
+    fn kelly_skips_bets_without_an_edge() {
+        let bets = [StakedBet { probability: 0.1, odds: 2.0, won: true }];
+        let sim = simulate_bankroll(100.0, StakingStrategy::Kelly { multiplier: 1.0, max_fraction: None }, &bets);
+
+        assert_eq!(sim.bets_placed, 0);
+        assert!(close(sim.ending_bankroll, 100.0));
+    }
+}
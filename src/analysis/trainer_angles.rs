@@ -0,0 +1,273 @@
+use crate::models::equipment::EquipmentEvent;
+use crate::models::model_config::TrainerAngleConfig;
+use crate::models::racecard::{Horse, Race};
+use crate::models::trainer_angles::{TrainerAngle, TrainerAngleKind, TrainerAngleSummary};
+
+pub fn parse_angle_kind(category: &str) -> TrainerAngleKind {
+    let c: String = category.to_lowercase().chars().filter(|ch| !ch.is_whitespace()).collect();
+    let has = |needles: &[&str]| needles.iter().any(|n| c.contains(n));
+
+    if has(&["1sttimestr", "firsttimestarter", "debut"]) {
+        TrainerAngleKind::FirstTimeStarter
+    } else if has(&["2ndcareer"]) {
+        TrainerAngleKind::SecondCareerStart
+    } else if has(&["1sttimelasix", "1stlasix", "firsttimelasix"]) {
+        TrainerAngleKind::FirstTimeLasix
+    } else if has(&["blinkerson", "blinkon"]) {
+        TrainerAngleKind::BlinkersOn
+    } else if has(&["blinkersoff", "blinkoff"]) {
+        TrainerAngleKind::BlinkersOff
+    } else if has(&["1stafterclm", "1stafterclaim", "1stclaim"]) {
+        TrainerAngleKind::FirstAfterClaim
+    } else if has(&["1ststartw/trn", "1stw/trn", "1stwithtrn", "1ststartwithtrainer"]) {
+        TrainerAngleKind::FirstWithTrainer
+    } else if has(&["2ndofflayoff"]) {
+        TrainerAngleKind::SecondOffLayoff
+    } else if has(&["1stofflayoff"]) {
+        TrainerAngleKind::FirstOffLayoff
+    } else if has(&["31-60"]) {
+        TrainerAngleKind::Days31To60
+    } else if has(&["61-180"]) {
+        TrainerAngleKind::Days61To180
+    } else if has(&["180+", "181+"]) {
+        TrainerAngleKind::Days180Plus
+    } else if has(&["shipper"]) {
+        TrainerAngleKind::Shipper
+    } else if has(&["dirttoturf", "dirt/turf"]) {
+        TrainerAngleKind::DirtToTurf
+    } else if has(&["turftodirt", "turf/dirt"]) {
+        TrainerAngleKind::TurfToDirt
+    } else if has(&["sprinttoroute", "sprint/route"]) {
+        TrainerAngleKind::SprintToRoute
+    } else if has(&["routetosprint", "route/sprint"]) {
+        TrainerAngleKind::RouteToSprint
+    } else {
+        TrainerAngleKind::Other
+    }
+}
+
+// The equipment changes an angle already scores, so they aren't counted twice.
+pub fn angle_equipment_events(kind: TrainerAngleKind) -> &'static [EquipmentEvent] {
+    match kind {
+        TrainerAngleKind::FirstTimeLasix => &[EquipmentEvent::FirstTimeLasix],
+        TrainerAngleKind::BlinkersOn => &[EquipmentEvent::BlinkersOn, EquipmentEvent::FirstTimeBlinkers],
+        TrainerAngleKind::BlinkersOff => &[EquipmentEvent::BlinkersOff],
+        _ => &[],
+    }
+}
+
+fn is_turf(surface: &str) -> bool {
+    surface.trim().eq_ignore_ascii_case("T")
+}
+
+pub fn angle_applies(
+    kind: TrainerAngleKind,
+    h: &Horse,
+    race: &Race,
+    equipment: &[EquipmentEvent],
+    track_code: Option<&str>,
+    config: &TrainerAngleConfig,
+) -> bool {
+    let last = h.past_performances.first();
+    let days = h.days_since_last_race;
+    let layoff = days.is_some_and(|d| d >= config.layoff_days);
+    let is_route = |distance: Option<i32>| distance.map(|d| d.abs() >= config.route_yards);
+
+    match kind {
+        TrainerAngleKind::FirstTimeStarter => h.past_performances.is_empty(),
+        TrainerAngleKind::SecondCareerStart => h.past_performances.len() == 1,
+        TrainerAngleKind::FirstTimeLasix | TrainerAngleKind::BlinkersOn | TrainerAngleKind::BlinkersOff => {
+            angle_equipment_events(kind).iter().any(|e| equipment.contains(e))
+        }
+        TrainerAngleKind::FirstAfterClaim => last.is_some_and(|pp| !pp.claimed_code.trim().is_empty()),
+        TrainerAngleKind::FirstWithTrainer => last.is_some_and(|pp| {
+            !pp.trainer.trim().is_empty() && !pp.trainer.trim().eq_ignore_ascii_case(h.todays_trainer.trim())
+        }),
+        TrainerAngleKind::FirstOffLayoff => last.is_some() && layoff,
+        TrainerAngleKind::SecondOffLayoff => {
+            !layoff && last.is_some_and(|pp| pp.days_since_last_race.is_some_and(|d| d >= config.layoff_days))
+        }
+        TrainerAngleKind::Days31To60 => last.is_some() && days.is_some_and(|d| (31..=60).contains(&d)),
+        TrainerAngleKind::Days61To180 => last.is_some() && days.is_some_and(|d| (61..=180).contains(&d)),
+        TrainerAngleKind::Days180Plus => last.is_some() && days.is_some_and(|d| d > 180),
+        TrainerAngleKind::Shipper => match (track_code, last) {
+            (Some(today), Some(pp)) => {
+                !pp.track_code.trim().is_empty() && !pp.track_code.trim().eq_ignore_ascii_case(today.trim())
+            }
+            _ => false,
+        },
+        TrainerAngleKind::DirtToTurf => is_turf(&race.surface) && last.is_some_and(|pp| !is_turf(&pp.surface)),
+        TrainerAngleKind::TurfToDirt => !is_turf(&race.surface) && last.is_some_and(|pp| is_turf(&pp.surface)),
+        TrainerAngleKind::SprintToRoute => {
+            is_route(race.distance) == Some(true) && last.is_some_and(|pp| is_route(pp.distance) == Some(false))
+        }
+        TrainerAngleKind::RouteToSprint => {
+            is_route(race.distance) == Some(false) && last.is_some_and(|pp| is_route(pp.distance) == Some(true))
+        }
+        TrainerAngleKind::Other => false,
+    }
+}
+
+pub fn matched_trainer_angles(
+    h: &Horse,
+    race: &Race,
+    equipment: &[EquipmentEvent],
+    track_code: Option<&str>,
+    config: &TrainerAngleConfig,
+) -> TrainerAngleSummary {
+    let angles: Vec<TrainerAngle> = h
+        .key_trainer_stats
+        .iter()
+        .filter(|k| k.starts.unwrap_or(0) >= config.min_starts)
+        .filter_map(|k| {
+            let kind = parse_angle_kind(&k.category);
+            if !angle_applies(kind, h, race, equipment, track_code, config) {
+                return None;
+            }
+
+            let score = k.win_pct.map(|w| config.win_pct.apply(w)).unwrap_or(0.0)
+                + k.roi.map(|r| config.roi.apply(r)).unwrap_or(0.0);
+
+            Some(TrainerAngle {
+                kind,
+                category: k.category.trim().to_string(),
+                starts: k.starts,
+                win_pct: k.win_pct,
+                roi: k.roi,
+                score,
+            })
+        })
+        .collect();
+
+    // Angles overlap (a layoff horse often matches two or three), so average rather than sum.
+    let score = if angles.is_empty() {
+        None
+    } else {
+        Some(angles.iter().map(|a| a.score).sum::<f64>() / angles.len() as f64)
+    };
+
+    TrainerAngleSummary { angles, score }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextual_speed_and_pace_model::rank_race_auto;
+    use crate::models::model_config::ModelConfig;
+    use crate::models::racecard::{KeyTrainerStat, PastPerformance};
+
+    fn stat(category: &str, starts: u32, win_pct: f64) -> KeyTrainerStat {
+        KeyTrainerStat {
+            category: category.to_string(),
+            starts: Some(starts),
+            win_pct: Some(win_pct),
+            roi: Some(0.0),
+            ..Default::default()
+        }
+    }
+
+    fn last_race(track_code: &str, surface: &str, distance: i32) -> PastPerformance {
+        PastPerformance {
+            race_date: "12/01/2025".to_string(),
+            track_code: track_code.to_string(),
+            surface: surface.to_string(),
+            distance: Some(distance),
+            bris_speed_rating: Some(85),
+            bris_4f_pace: Some(85),
+            bris_late_pace: Some(85),
+            ..Default::default()
+        }
+    }
+
+    fn dirt_sprint() -> Race {
+        Race { distance: Some(1320), surface: "D".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn parses_key_trainer_categories() {
+        assert_eq!(parse_angle_kind("1st time Lasix"), TrainerAngleKind::FirstTimeLasix);
+        assert_eq!(parse_angle_kind("2nd off layoff"), TrainerAngleKind::SecondOffLayoff);
+        assert_eq!(parse_angle_kind("61-180 Days Away"), TrainerAngleKind::Days61To180);
+        assert_eq!(parse_angle_kind("Shipper"), TrainerAngleKind::Shipper);
+        assert_eq!(parse_angle_kind("Dirt to Turf"), TrainerAngleKind::DirtToTurf);
+        assert_eq!(parse_angle_kind("Graded Stakes"), TrainerAngleKind::Other);
+    }
+
+    #[test]
+    fn shipper_needs_todays_track() {
+        let config = TrainerAngleConfig::default();
+        let horse = Horse { past_performances: vec![last_race("AQU", "D", 1320)], ..Default::default() };
+        let race = dirt_sprint();
+
+        let applies = |track_code| angle_applies(TrainerAngleKind::Shipper, &horse, &race, &[], track_code, &config);
+
+        assert!(applies(Some("SAR")));
+        assert!(!applies(Some("aqu")));
+        assert!(!applies(None));
+    }
+
+    #[test]
+    fn layoff_and_distance_switches_follow_the_config() {
+        let config = TrainerAngleConfig::default();
+        // The last start was the first back from a layoff.
+        let mut back = last_race("SAR", "D", 1320);
+        back.days_since_last_race = Some(config.layoff_days + 10);
+        let horse = Horse {
+            days_since_last_race: Some(21),
+            past_performances: vec![back, last_race("SAR", "D", 1320)],
+            ..Default::default()
+        };
+        let route = Race { distance: Some(config.route_yards), ..dirt_sprint() };
+
+        let applies = |kind, race: &Race| angle_applies(kind, &horse, race, &[], Some("SAR"), &config);
+
+        assert!(applies(TrainerAngleKind::SecondOffLayoff, &route));
+        assert!(!applies(TrainerAngleKind::FirstOffLayoff, &route));
+        assert!(applies(TrainerAngleKind::SprintToRoute, &route));
+        assert!(!applies(TrainerAngleKind::SprintToRoute, &dirt_sprint()));
+    }
+
+    #[test]
+    fn matched_angles_skip_thin_samples_and_average_the_rest() {
+        let config = TrainerAngleConfig::default();
+        let horse = Horse {
+            days_since_last_race: Some(90),
+            past_performances: vec![last_race("AQU", "D", 1320)],
+            key_trainer_stats: vec![
+                stat("1st off layoff", 40, 32.0),
+                stat("61-180 Days Away", 30, 12.0),
+                stat("Shipper", config.min_starts - 1, 50.0),
+            ],
+            ..Default::default()
+        };
+
+        let summary = matched_trainer_angles(&horse, &dirt_sprint(), &[], Some("SAR"), &config);
+
+        let kinds: Vec<TrainerAngleKind> = summary.angles.iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, vec![TrainerAngleKind::FirstOffLayoff, TrainerAngleKind::Days61To180]);
+        let expected = (config.win_pct.apply(32.0) + config.win_pct.apply(12.0)) / 2.0;
+        assert!((summary.score.unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ranking_with_a_track_code_matches_the_shipper_angle() {
+        let config = ModelConfig { trainer_angles: Some(TrainerAngleConfig::default()), ..ModelConfig::default() };
+        let shipper = Horse {
+            program_number: "1".to_string(),
+            past_performances: vec![last_race("AQU", "D", 1320); 3],
+            key_trainer_stats: vec![stat("Shipper", 25, 30.0)],
+            ..Default::default()
+        };
+        let race = Race {
+            four_f_bris_pace_par: Some(85),
+            bris_speed_for_class: Some(85),
+            bris_late_pace_par: Some(85),
+            horses: vec![shipper],
+            ..dirt_sprint()
+        };
+
+        let ranked = rank_race_auto(&race, Some("01/01/2026"), Some("SAR"), &config);
+
+        assert_eq!(ranked.horses[0].trainer_angles, vec!["Shipper".to_string()]);
+    }
+}
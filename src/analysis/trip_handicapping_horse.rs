@@ -39,10 +39,10 @@ fn max_path_from_text(text: &str) -> Option<u32> {
             let aa: u32 = a.as_str().parse().ok()?;
             let bb: u32 = b.as_str().parse().ok()?;
             best = Some(best.unwrap_or(0).max(aa.max(bb)));
-        } else if let Some(x) = cap.get(3) {
-            if let Ok(v) = x.as_str().parse::<u32>() {
-                best = Some(best.unwrap_or(0).max(v));
-            }
+        } else if let Some(x) = cap.get(3)
+            && let Ok(v) = x.as_str().parse::<u32>()
+        {
+            best = Some(best.unwrap_or(0).max(v));
         }
     }
 
@@ -117,13 +117,13 @@ fn raw_trip_score(text: &str) -> TripScore {
         why.push("bump/brush".into());
     }
 
-    if let Some(p) = max_path_from_text(text) {
-        if p >= 3 {
-            let extra = (p as f64) - 2.0;
-            let add = extra * 0.65;
-            raw += add;
-            why.push(format!("wide_{}w", p));
-        }
+    if let Some(p) = max_path_from_text(text)
+        && p >= 3
+    {
+        let extra = (p as f64) - 2.0;
+        let add = extra * 0.65;
+        raw += add;
+        why.push(format!("wide_{}w", p));
     }
 
     if contains_any(
@@ -218,11 +218,11 @@ fn apply_context_adjust(mut ts: TripScore, pp: &PastPerformance) -> TripScore {
         ts.why.push("stopped_clip".into());
     }
 
-    if let (Some(p2), Some(lp)) = (pp.bris_2f_pace, pp.bris_late_pace) {
-        if p2 >= 85 && lp > 0 && lp <= 70 {
-            adj += 0.4;
-            ts.why.push("fast_early_context".into());
-        }
+    if let (Some(p2), Some(lp)) = (pp.bris_2f_pace, pp.bris_late_pace)
+        && p2 >= 85 && lp > 0 && lp <= 70
+    {
+        adj += 0.4;
+        ts.why.push("fast_early_context".into());
     }
 
     ts.adj = adj.max(0.0);
@@ -299,10 +299,10 @@ fn bad_trip_reason(text: &str) -> Option<&'static str> {
 }
 
 fn map_good_reason(reason: &str) -> String {
-    if let Some(stripped) = reason.strip_prefix("wide_") {
-        if let Some(w) = stripped.strip_suffix('w') {
-            return format!("wide {w}w");
-        }
+    if let Some(stripped) = reason.strip_prefix("wide_")
+        && let Some(w) = stripped.strip_suffix('w')
+    {
+        return format!("wide {w}w");
     }
 
     match reason {
@@ -340,11 +340,11 @@ fn classify_trip(pp: &PastPerformance) -> (String, i32) {
     let text = merged_trip_text(pp);
     let trimmed = text.trim();
 
-    if let Some(pos) = finish_position_number(pp) {
-        if pos <= 3 {
-            let label = finish_position_label(pos).unwrap_or("top-3 finish");
-            return (format!("Good: {label}"), 1);
-        }
+    if let Some(pos) = finish_position_number(pp)
+        && pos <= 3
+    {
+        let label = finish_position_label(pos).unwrap_or("top-3 finish");
+        return (format!("Good: {label}"), 1);
     }
 
     if trimmed.is_empty() {
@@ -392,6 +392,7 @@ fn classify_trip(pp: &PastPerformance) -> (String, i32) {
     }
 }
 
+#[allow(clippy::ptr_arg)]
 pub fn trip_data_for_horse(horse: &Horse, race_date: &String) -> Option<TripResult> {
     let race_day = parse_race_date(race_date)?;
 
@@ -427,13 +428,13 @@ pub fn trip_data_for_horse(horse: &Horse, race_date: &String) -> Option<TripResu
         score += w * (*grade);
     }
 
-    let mut comments = vec![
+    let mut comments = [
         "".to_string(),
         "".to_string(),
         "".to_string(),
     ];
 
-    let mut days_back =vec![
+    let mut days_back = [
         0,
         0,
         0,
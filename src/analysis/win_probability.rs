@@ -0,0 +1,215 @@
+use crate::analysis::backtest::{apply_result_scratches, normalize_program};
+use crate::contextual_speed_and_pace_model::{RankContext, rank_race_with_context, win_probabilities_with_unscored};
+use crate::models::backtest::BacktestRace;
+use crate::models::model_config::ModelConfig;
+use crate::models::win_probability::TemperatureFit;
+use crate::utils::transformers::Transformers;
+
+struct ScoredField {
+    scores: Vec<f64>,
+    winners: Vec<usize>,
+    unscored: usize,
+    unscored_winners: usize,
+}
+
+impl ScoredField {
+    fn runners(&self) -> usize {
+        self.scores.len() + self.unscored
+    }
+}
+
+fn scored_fields(races: &[BacktestRace], config: &ModelConfig) -> Vec<ScoredField> {
+    let mut fields = Vec::new();
+
+    for entry in races {
+        let winners: Vec<String> = entry.result.winners().iter().map(|w| normalize_program(&w.program_number)).collect();
+        if winners.is_empty() {
+            continue;
+        }
+
+        let race = apply_result_scratches(&entry.race, &entry.result);
+        let racecard_date = Transformers::yyyymmdd_to_mmddyyyy(&entry.date);
+        let ctx = RankContext {
+            racecard_date: racecard_date.as_deref(),
+            track_code: Some(&entry.track_code),
+            sire_stats: &entry.sire_stats,
+            racecard: entry.racecard.as_deref(),
+        };
+        let ranked = rank_race_with_context(&race, &ctx, config);
+
+        let runners: Vec<(String, f64)> = ranked
+            .horses
+            .iter()
+            .filter(|h| h.win_probability.is_some())
+            .filter_map(|h| h.score.map(|s| (normalize_program(&h.program_number), s)))
+            .collect();
+
+        if runners.len() < 2 {
+            continue;
+        }
+        let starters: Vec<String> = race
+            .horses
+            .iter()
+            .filter(|h| !h.scratched)
+            .map(|h| normalize_program(&h.program_number))
+            .collect();
+        let unscored = starters.len().saturating_sub(runners.len());
+        let winner_indexes: Vec<usize> = runners
+            .iter()
+            .enumerate()
+            .filter(|(_, (p, _))| winners.contains(p))
+            .map(|(i, _)| i)
+            .collect();
+        // A winner the model couldn't score still counts, against its share of the unscored mass.
+        let unscored_winners = starters
+            .iter()
+            .filter(|p| winners.contains(p) && !runners.iter().any(|(r, _)| r == *p))
+            .count()
+            .min(unscored);
+        if !winner_indexes.is_empty() || unscored_winners > 0 {
+            fields.push(ScoredField {
+                scores: runners.into_iter().map(|(_, s)| s).collect(),
+                winners: winner_indexes,
+                unscored,
+                unscored_winners,
+            });
+        }
+    }
+
+    fields
+}
+
+fn mean_log_loss(fields: &[ScoredField], temperature: f64) -> f64 {
+    let total: f64 = fields
+        .iter()
+        .map(|f| {
+            // In a dead heat any of the tied horses counts as the winner.
+            let p = win_probabilities_with_unscored(&f.scores, f.unscored, temperature);
+            let mut won = f.winners.iter().map(|&i| p[i]).sum::<f64>();
+            if f.unscored_winners > 0 {
+                let unscored_mass = (1.0 - p.iter().sum::<f64>()).max(0.0);
+                won += unscored_mass * f.unscored_winners as f64 / f.unscored as f64;
+            }
+            -won.max(1e-12).ln()
+        })
+        .sum();
+    total / fields.len() as f64
+}
+
+pub fn fit_win_probability_temperature(races: &[BacktestRace], config: &ModelConfig) -> Option<TemperatureFit> {
+    let fields = scored_fields(races, config);
+    if fields.is_empty() {
+        return None;
+    }
+
+    let golden = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (0.05f64.ln(), 20f64.ln());
+    for _ in 0..60 {
+        let a = hi - golden * (hi - lo);
+        let b = lo + golden * (hi - lo);
+        if mean_log_loss(&fields, a.exp()) <= mean_log_loss(&fields, b.exp()) {
+            hi = b;
+        } else {
+            lo = a;
+        }
+    }
+
+    let temperature = ((lo + hi) / 2.0).exp();
+    let uniform_log_loss = fields.iter().map(|f| (f.runners() as f64).ln()).sum::<f64>() / fields.len() as f64;
+
+    Some(TemperatureFit {
+        temperature,
+        races: fields.len() as u32,
+        log_loss: mean_log_loss(&fields, temperature),
+        uniform_log_loss,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::models::race_result::{RaceResult, ResultStarter};
+    use crate::models::racecard::{Horse, PastPerformance, Race};
+
+    fn horse(id: i64, program_number: &str, speed: Option<u32>) -> Horse {
+        let pp = PastPerformance {
+            race_date: "12/01/2025".to_string(),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            bris_speed_rating: speed,
+            bris_4f_pace: speed,
+            bris_late_pace: speed,
+            ..Default::default()
+        };
+        Horse {
+            id,
+            program_number: program_number.to_string(),
+            past_performances: if speed.is_some() { vec![pp; 3] } else { Vec::new() },
+            ..Default::default()
+        }
+    }
+
+    fn entry(winner: &str) -> BacktestRace {
+        let race = Race {
+            id: 1,
+            race_number: Some(1),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            four_f_bris_pace_par: Some(85),
+            bris_speed_for_class: Some(85),
+            bris_late_pace_par: Some(85),
+            horses: vec![horse(1, "1", Some(90)), horse(2, "2", Some(80)), horse(3, "3", None)],
+            ..Default::default()
+        };
+        let starters = ["1", "2", "3"]
+            .iter()
+            .enumerate()
+            .map(|(i, p)| ResultStarter {
+                horse_id: Some(i as i64 + 1),
+                program_number: p.to_string(),
+                finish_position: Some(if *p == winner { 1 } else { 2 + i as u32 }),
+                ..Default::default()
+            })
+            .collect();
+        BacktestRace {
+            racecard_id: 1,
+            track_code: "SAR".to_string(),
+            date: "20260101".to_string(),
+            race,
+            result: RaceResult { starters, ..Default::default() },
+            racecard: None,
+            sire_stats: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn keeps_races_won_by_an_unscored_runner() {
+        let fields = scored_fields(&[entry("3")], &ModelConfig::default());
+
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].scores.len(), 2);
+        assert!(fields[0].winners.is_empty());
+        assert_eq!((fields[0].unscored, fields[0].unscored_winners), (1, 1));
+        assert_eq!(fields[0].runners(), 3);
+    }
+
+    #[test]
+    fn an_unscored_winner_is_charged_its_share_of_the_unscored_mass() {
+        let field = ScoredField { scores: vec![1.0, 0.0], winners: Vec::new(), unscored: 2, unscored_winners: 1 };
+        let p = win_probabilities_with_unscored(&field.scores, field.unscored, 1.0);
+        let share = (1.0 - p.iter().sum::<f64>()) / 2.0;
+
+        let loss = mean_log_loss(&[field], 1.0);
+
+        assert!((loss + share.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn uniform_baseline_counts_unscored_runners() {
+        let fit = fit_win_probability_temperature(&[entry("1"), entry("3")], &ModelConfig::default()).unwrap();
+
+        assert_eq!(fit.races, 2);
+        assert!((fit.uniform_log_loss - 3f64.ln()).abs() < 1e-9);
+    }
+}
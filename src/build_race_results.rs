@@ -0,0 +1,280 @@
+//! Reads Equibase-style result chart XML into `RaceResult`s.
+//!
+//! Elements read (everything else is ignored):
+//! - `CHART[@RACE_DATE]` (YYYY-MM-DD) and `CHART/TRACK/CODE`
+//! - `RACE[@NUMBER]` with `DISTANCE` + `DIST_UNIT` (F = hundredths of a furlong, Y = yards; without a
+//!   known unit the distance is left empty),
+//!   `COURSE_ID` or `SURFACE`, `TRK_COND` and `WIN_TIME` (seconds)
+//! - `RACE/ENTRY` with `NAME`, `PROGRAM_NUM`, `POST_POS`, `OFFICIAL_FIN`, `DOLLAR_ODDS`,
+//!   `WIN_PAYOFF`, `PLACE_PAYOFF`, `SHOW_PAYOFF` and `POINT_OF_CALL[@WHICH="FINAL"]/LENGTHS`
+//!   (lengths ahead of the next finisher)
+//! - `RACE/EXOTIC_WAGERS/WAGER` with `WAGER_TYPE`, `BASE_AMOUNT`, `WINNERS`, `PAYOFF`, `POOL_TOTAL`
+//!
+//! Win, place and show payoffs are for a $2 base.
+
+use crate::models::race_result::{RaceResult, ResultPayout, ResultStarter};
+use roxmltree::{Document, Node};
+use std::path::Path;
+use tokio::fs;
+
+pub async fn build_race_results(path: String) -> Result<Vec<RaceResult>, String> {
+    let contents = fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read chart file: {}", e))?;
+
+    let source_file = Path::new(&path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    parse_chart_xml(&contents, &source_file)
+}
+
+pub fn parse_chart_xml(xml: &str, source_file: &str) -> Result<Vec<RaceResult>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Failed to parse chart XML: {}", e))?;
+    let chart = doc.root_element();
+
+    if !chart.has_tag_name("CHART") {
+        return Err(format!("Expected CHART root element, found {}", chart.tag_name().name()));
+    }
+
+    let race_date = chart
+        .attribute("RACE_DATE")
+        .map(|d| d.trim().replace('-', ""))
+        .filter(|d| d.len() == 8)
+        .ok_or_else(|| "Chart is missing a RACE_DATE attribute".to_string())?;
+
+    let track_code = child(chart, "TRACK")
+        .and_then(|t| child_text(t, "CODE"))
+        .map(|c| c.to_lowercase())
+        .ok_or_else(|| "Chart is missing TRACK/CODE".to_string())?;
+
+    let mut results = Vec::new();
+
+    for race in chart.children().filter(|n| n.has_tag_name("RACE")) {
+        let race_number = race.attribute("NUMBER").and_then(|n| n.trim().parse::<u32>().ok());
+
+        let mut starters: Vec<ResultStarter> = race
+            .children()
+            .filter(|n| n.has_tag_name("ENTRY"))
+            .map(starter_from_entry)
+            .collect();
+        fill_beaten_lengths(&mut starters);
+
+        let mut payouts = straight_payouts(&starters);
+        if let Some(wagers) = child(race, "EXOTIC_WAGERS") {
+            for wager in wagers.children().filter(|n| n.has_tag_name("WAGER")) {
+                payouts.push(ResultPayout {
+                    id: 0,
+                    race_result_id: 0,
+                    pool: pool_name(&child_text(wager, "WAGER_TYPE").unwrap_or_default()),
+                    base_amount: child_f64(wager, "BASE_AMOUNT"),
+                    winning_numbers: child_text(wager, "WINNERS").unwrap_or_default(),
+                    payoff: child_f64(wager, "PAYOFF"),
+                    pool_total: child_f64(wager, "POOL_TOTAL"),
+                });
+            }
+        }
+
+        results.push(RaceResult {
+            id: 0,
+            race_id: None,
+            track_code: track_code.clone(),
+            race_date: race_date.clone(),
+            race_number,
+            distance: distance_in_yards(race),
+            surface: child_text(race, "COURSE_ID")
+                .or_else(|| child_text(race, "SURFACE"))
+                .unwrap_or_default(),
+            track_condition: child_text(race, "TRK_COND").unwrap_or_default(),
+            final_time: child_f64(race, "WIN_TIME"),
+            source_file: source_file.to_string(),
+            starters,
+            payouts,
+        });
+    }
+
+    Ok(results)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn child_text(node: Node, tag: &str) -> Option<String> {
+    child(node, tag)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn child_f64(node: Node, tag: &str) -> Option<f64> {
+    child_text(node, tag).and_then(|t| t.replace(',', "").parse::<f64>().ok())
+}
+
+fn child_u32(node: Node, tag: &str) -> Option<u32> {
+    child_text(node, tag).and_then(|t| t.parse::<u32>().ok())
+}
+
+fn distance_in_yards(race: Node) -> Option<i32> {
+    let distance = child_f64(race, "DISTANCE")?;
+    match child_text(race, "DIST_UNIT")?.to_uppercase().as_str() {
+        "Y" => Some(distance.round() as i32),
+        "F" => Some((distance / 100.0 * 220.0).round() as i32),
+        _ => None,
+    }
+}
+
+fn starter_from_entry(entry: Node) -> ResultStarter {
+    let margin = entry
+        .children()
+        .filter(|n| n.has_tag_name("POINT_OF_CALL"))
+        .find(|n| n.attribute("WHICH").map(|w| w.eq_ignore_ascii_case("FINAL")).unwrap_or(false))
+        .and_then(|n| child_f64(n, "LENGTHS"));
+
+    ResultStarter {
+        id: 0,
+        race_result_id: 0,
+        horse_id: None,
+        program_number: child_text(entry, "PROGRAM_NUM").unwrap_or_default(),
+        horse_name: child_text(entry, "NAME").unwrap_or_default(),
+        post_position: child_u32(entry, "POST_POS"),
+        finish_position: child_u32(entry, "OFFICIAL_FIN"),
+        margin,
+        beaten_lengths: None,
+        final_odds: child_f64(entry, "DOLLAR_ODDS"),
+        win_payoff: child_f64(entry, "WIN_PAYOFF").filter(|p| *p > 0.0),
+        place_payoff: child_f64(entry, "PLACE_PAYOFF").filter(|p| *p > 0.0),
+        show_payoff: child_f64(entry, "SHOW_PAYOFF").filter(|p| *p > 0.0),
+    }
+}
+
+fn fill_beaten_lengths(starters: &mut [ResultStarter]) {
+    let mut order: Vec<usize> = (0..starters.len())
+        .filter(|&i| starters[i].finish_position.is_some())
+        .collect();
+    order.sort_by_key(|&i| starters[i].finish_position);
+
+    let mut behind = 0.0;
+    for i in order {
+        starters[i].beaten_lengths = Some(behind);
+        behind += starters[i].margin.unwrap_or(0.0);
+    }
+}
+
+fn straight_payouts(starters: &[ResultStarter]) -> Vec<ResultPayout> {
+    let mut payouts = Vec::new();
+
+    for s in starters {
+        for (pool, payoff) in [("Win", s.win_payoff), ("Place", s.place_payoff), ("Show", s.show_payoff)] {
+            if let Some(p) = payoff {
+                payouts.push(ResultPayout {
+                    id: 0,
+                    race_result_id: 0,
+                    pool: pool.to_string(),
+                    base_amount: Some(2.0),
+                    winning_numbers: s.program_number.clone(),
+                    payoff: Some(p),
+                    pool_total: None,
+                });
+            }
+        }
+    }
+
+    payouts
+}
+
+fn pool_name(raw: &str) -> String {
+    let r = raw.trim();
+    match r.to_uppercase().as_str() {
+        "E" | "EX" | "EXACTA" => "Exacta".to_string(),
+        "T" | "TR" | "TRI" | "TRIFECTA" => "Trifecta".to_string(),
+        "S" | "SU" | "SUPER" | "SUPERFECTA" => "Superfecta".to_string(),
+        "D" | "DD" | "DAILY DOUBLE" => "Daily Double".to_string(),
+        "Q" | "QU" | "QUINELLA" => "Quinella".to_string(),
+        "P3" | "PICK 3" => "Pick 3".to_string(),
+        "P4" | "PICK 4" => "Pick 4".to_string(),
+        "P5" | "PICK 5" => "Pick 5".to_string(),
+        "P6" | "PICK 6" => "Pick 6".to_string(),
+        _ => r.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = r#"<?xml version="1.0"?>
+<CHART RACE_DATE="2026-01-01">
+ <TRACK><CODE>SAR</CODE></TRACK>
+ <RACE NUMBER="1">
+  <DISTANCE>600</DISTANCE><DIST_UNIT>F</DIST_UNIT><COURSE_ID>D</COURSE_ID><TRK_COND>FT</TRK_COND><WIN_TIME>70.85</WIN_TIME>
+  <ENTRY><NAME>Foo</NAME><PROGRAM_NUM>1</PROGRAM_NUM><POST_POS>1</POST_POS><OFFICIAL_FIN>1</OFFICIAL_FIN><DOLLAR_ODDS>2.30</DOLLAR_ODDS><WIN_PAYOFF>6.60</WIN_PAYOFF><PLACE_PAYOFF>3.20</PLACE_PAYOFF><POINT_OF_CALL WHICH="FINAL"><LENGTHS>1.5</LENGTHS></POINT_OF_CALL></ENTRY>
+  <ENTRY><NAME>Bar</NAME><PROGRAM_NUM>2</PROGRAM_NUM><POST_POS>2</POST_POS><OFFICIAL_FIN>2</OFFICIAL_FIN><DOLLAR_ODDS>5.00</DOLLAR_ODDS><PLACE_PAYOFF>4.20</PLACE_PAYOFF><POINT_OF_CALL WHICH="FINAL"><LENGTHS>2</LENGTHS></POINT_OF_CALL></ENTRY>
+  <ENTRY><NAME>Baz</NAME><PROGRAM_NUM>3</PROGRAM_NUM><POST_POS>3</POST_POS><OFFICIAL_FIN>3</OFFICIAL_FIN><DOLLAR_ODDS>8.00</DOLLAR_ODDS></ENTRY>
+  <EXOTIC_WAGERS><WAGER><WAGER_TYPE>E</WAGER_TYPE><BASE_AMOUNT>2</BASE_AMOUNT><WINNERS>1-2</WINNERS><PAYOFF>24.80</PAYOFF><POOL_TOTAL>100,000</POOL_TOTAL></WAGER></EXOTIC_WAGERS>
+ </RACE>
+ <RACE NUMBER="2">
+  <DISTANCE>1760</DISTANCE><DIST_UNIT>Y</DIST_UNIT><SURFACE>T</SURFACE>
+  <ENTRY><NAME>Dead</NAME><PROGRAM_NUM>1A</PROGRAM_NUM><OFFICIAL_FIN>1</OFFICIAL_FIN></ENTRY>
+  <ENTRY><NAME>Heat</NAME><PROGRAM_NUM>4</PROGRAM_NUM><OFFICIAL_FIN>1</OFFICIAL_FIN></ENTRY>
+ </RACE>
+ <RACE NUMBER="3">
+  <DISTANCE>800</DISTANCE><COURSE_ID>D</COURSE_ID>
+  <ENTRY><NAME>Solo</NAME><PROGRAM_NUM>1</PROGRAM_NUM><OFFICIAL_FIN>1</OFFICIAL_FIN></ENTRY>
+ </RACE>
+</CHART>"#;
+
+    #[test]
+    fn parses_races_starters_and_payouts() {
+        let results = parse_chart_xml(CHART, "sar0101.xml").unwrap();
+        assert_eq!(results.len(), 3);
+
+        let race = &results[0];
+        assert_eq!(race.track_code, "sar");
+        assert_eq!(race.race_date, "20260101");
+        assert_eq!(race.race_number, Some(1));
+        assert_eq!(race.distance, Some(1320));
+        assert_eq!(race.surface, "D");
+        assert_eq!(race.final_time, Some(70.85));
+        assert_eq!(race.source_file, "sar0101.xml");
+
+        let beaten: Vec<Option<f64>> = race.starters.iter().map(|s| s.beaten_lengths).collect();
+        assert_eq!(beaten, vec![Some(0.0), Some(1.5), Some(3.5)]);
+
+        let pools: Vec<&str> = race.payouts.iter().map(|p| p.pool.as_str()).collect();
+        assert_eq!(pools, vec!["Win", "Place", "Place", "Exacta"]);
+        assert_eq!(race.payouts[3].pool_total, Some(100_000.0));
+
+        assert_eq!(results[1].distance, Some(1760));
+        assert_eq!(results[1].surface, "T");
+        assert_eq!(results[2].distance, None);
+    }
+
+    #[test]
+    fn dead_heat_returns_every_winner() {
+        let results = parse_chart_xml(CHART, "sar0101.xml").unwrap();
+        let winners: Vec<&str> = results[1].winners().iter().map(|s| s.program_number.as_str()).collect();
+        assert_eq!(winners, vec!["1A", "4"]);
+        assert_eq!(results[0].winners().len(), 1);
+    }
+
+    #[test]
+    fn rejects_charts_without_a_date() {
+        let err = parse_chart_xml("<CHART><TRACK><CODE>SAR</CODE></TRACK></CHART>", "x.xml").unwrap_err();
+        assert!(err.contains("RACE_DATE"));
+    }
+
+    #[tokio::test]
+    async fn builds_results_from_a_file() {
+        let path = std::env::temp_dir().join(format!("railbreaker-chart-{}.xml", std::process::id()));
+        std::fs::write(&path, CHART).unwrap();
+
+        let results = build_race_results(path.to_string_lossy().to_string()).await;
+        std::fs::remove_file(&path).unwrap();
+
+        let results = results.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].source_file.starts_with("railbreaker-chart-"));
+    }
+}
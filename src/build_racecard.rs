@@ -44,7 +44,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
             let race = Race {
                 id: 0,
                 racecard_id: 0,
-                race_number: race_number,
+                race_number,
                 distance: line[SF_DISTANCE].parse::<i32>().ok(),
                 surface: line[SF_SURFACE].clone(),
                 race_type: line[SF_RACE_TYPE].clone(),
@@ -98,6 +98,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
         let mut horse = Horse {
             id: 0,
             race_id: 0,
+            horse_identity_id: None,
             scratched: false,
             trip_handicapping_info: String::new(),
             post_position: line[SF_POST_POSITION].parse::<u32>().ok(),
@@ -271,7 +272,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
         };
 
         for j in 0..12 {
-            if &line[SF_WORKOUT_DATE + j] == "" {
+            if line[SF_WORKOUT_DATE + j].is_empty() {
                 continue;
             }
 
@@ -295,7 +296,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
         }
 
         for j in 0..10 {
-            if &line[SF_PP_RACE_DATE + j] == "" {
+            if line[SF_PP_RACE_DATE + j].is_empty() {
                 continue;
             }
 
@@ -303,7 +304,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
                 id: 0,
                 horse_id: 0,
                 race_date: Transformers::yyyymmdd_to_mmddyyyy(&line[SF_PP_RACE_DATE + j])
-                    .unwrap_or_else(|| "".to_string()),
+                    .unwrap_or_default(),
                 days_since_last_race: line[SF_PP_NUMBER_OF_DAYS_SINCE_LAST_RACE + j]
                     .parse::<u32>().ok(),
                 track_code: line[SF_PP_TRACK_CODE + j].clone(),
@@ -452,7 +453,7 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
         }
 
         for j in 0..6 {
-            if &lines[0][SF_KEY_TRAINER_STAT + j * 5] == "" {
+            if lines[0][SF_KEY_TRAINER_STAT + j * 5].is_empty() {
                 continue;
             }
 
@@ -484,13 +485,13 @@ pub async fn build_racecard(path: String, zip_file_name: String) -> Result<Racec
 
     let racecard = Racecard {
         id: 0,
-        zip_file_name: zip_file_name,
+        zip_file_name,
         track_code: track_code.clone(),
         track: track_name.to_string(),
         date: lines[0][SF_RACE_DATE].clone(),
         long_date: Transformers::prepend_weekday(&lines[0][SF_RACE_DATE])
             .unwrap_or_else(|| lines[0][SF_RACE_DATE].clone()),
-        races: races,
+        races,
     };
 
     fs::remove_file(&path)
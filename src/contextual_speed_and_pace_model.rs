@@ -1,12 +1,29 @@
-use crate::models::racecard::{Horse, PastPerformance, Race};
+use crate::analysis::class_analysis::class_change;
+use crate::analysis::connections::analyze_connections;
+use crate::models::connections::ConnectionFactor;
+use crate::analysis::form_cycle::analyze_form_cycle;
+use crate::analysis::pedigree_switch::analyze_switches;
+use crate::analysis::equipment::decode_equipment_events;
+use crate::analysis::trainer_angles::{angle_equipment_events, matched_trainer_angles};
+use crate::models::equipment::EquipmentEvent;
+use crate::models::pedigree_switch::{SireStats, SwitchSuitability};
+use crate::models::form_cycle::{FormCycle, FormState};
+use crate::models::model_config::{
+    DebutModelConfig, FigureAdjustmentConfig, LayoffPenalty, ModelConfig, SurfaceModelConfig,
+};
+use crate::models::racecard::{Horse, PastPerformance, Race, Racecard};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub const MODEL_VERSION: &str = "contextual-speed-pace-2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SurfaceMode {
     Dirt,
     Turf,
+    Synthetic,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -30,6 +47,18 @@ pub struct RepFigs {
     pub rep_speed: Option<f64>,
     pub rep_early: Option<f64>,
     pub rep_late: Option<f64>,
+    #[serde(default)]
+    pub raw_rep_speed: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjustedFigure {
+    pub raw: f64,
+    pub variant: f64,
+    pub condition: f64,
+    pub track_class: f64,
+    pub surface: f64,
+    pub adjusted: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +68,36 @@ pub struct WorkoutSig {
     pub score: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreComponent {
+    pub name: String,
+    pub raw: Option<f64>,
+    pub weight: Option<f64>,
+    pub contribution: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub components: Vec<ScoreComponent>,
+    pub total: f64,
+}
+
+impl ScoreBreakdown {
+    pub fn push(&mut self, name: &str, raw: Option<f64>, weight: Option<f64>, contribution: f64) {
+        self.total += contribution;
+        self.components.push(ScoreComponent {
+            name: name.to_string(),
+            raw,
+            weight,
+            contribution,
+        });
+    }
+
+    pub fn contribution(&self, name: &str) -> Option<f64> {
+        self.components.iter().find(|c| c.name == name).map(|c| c.contribution)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HorseRank {
     pub program_number: String,
@@ -50,6 +109,23 @@ pub struct HorseRank {
     pub score: Option<f64>,
     pub rep: RepFigs,
     pub workout: WorkoutSig,
+    pub breakdown: Option<ScoreBreakdown>,
+    #[serde(default)]
+    pub low_confidence: bool,
+    pub win_probability: Option<f64>,
+    pub fair_odds: Option<f64>,
+    #[serde(default)]
+    pub angle_score: Option<f64>,
+    #[serde(default)]
+    pub trainer_angles: Vec<String>,
+    #[serde(default)]
+    pub equipment_events: Vec<EquipmentEvent>,
+    #[serde(default)]
+    pub form_cycle: Option<FormCycle>,
+    #[serde(default)]
+    pub switches: Vec<SwitchSuitability>,
+    #[serde(default)]
+    pub connections: Vec<ConnectionFactor>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,7 +171,52 @@ pub struct RaceMeta {
     pub race_rank_result: RaceRankResult,
 }
 
-#[derive(Debug, Clone, Copy)]
+// Racecard-level inputs that a `Race` doesn't carry on its own.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RankContext<'a> {
+    pub racecard_date: Option<&'a str>,
+    pub track_code: Option<&'a str>,
+    pub sire_stats: &'a [SireStats],
+    pub racecard: Option<&'a Racecard>,
+}
+
+impl<'a> RankContext<'a> {
+    pub fn new(racecard_date: Option<&'a str>) -> Self {
+        Self { racecard_date, ..Self::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackScenario {
+    pub wet_track: bool,
+    pub sealed: bool,
+    pub off_turf: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioRankChange {
+    pub program_number: String,
+    pub horse_name: String,
+    pub main_track_only: bool,
+    pub fast_rank: Option<u32>,
+    pub projected_rank: Option<u32>,
+    pub rank_change: Option<i32>,
+    pub fast_score: Option<f64>,
+    pub projected_score: Option<f64>,
+    pub wet_adjustment: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioRankResult {
+    pub scenario: TrackScenario,
+    pub fast: RaceRankResult,
+    pub projected: RaceRankResult,
+    pub changes: Vec<ScenarioRankChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WinBetOptions {
     pub min_top_score: f64,
     pub min_gap: f64,
@@ -110,6 +231,19 @@ impl Default for WinBetOptions {
     }
 }
 
+// Custom figures are computed outside the ranking, so their settings don't change the model's output.
+pub fn params_hash(config: &ModelConfig) -> String {
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("custom_figures");
+    }
+    let json = value.to_string();
+    Sha256::digest(json.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 fn clamp(v: f64, lo: f64, hi: f64) -> f64 {
     v.max(lo).min(hi)
 }
@@ -141,11 +275,11 @@ fn weighted_avg(values: &[Option<f64>], weights: &[f64]) -> Option<f64> {
     let mut den = 0.0;
 
     for i in 0..values.len().min(weights.len()) {
-        if let Some(v) = values[i] {
-            if v.is_finite() {
-                num += v * weights[i];
-                den += weights[i];
-            }
+        if let Some(v) = values[i]
+            && v.is_finite()
+        {
+            num += v * weights[i];
+            den += weights[i];
         }
     }
 
@@ -156,11 +290,29 @@ fn parse_mmddyyyy(s: &str) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(s, "%m/%d/%Y").ok()
 }
 
-fn surface_matches(mode: SurfaceMode, pp_surface: &str) -> bool {
-    let s = pp_surface.trim().to_uppercase();
+fn surface_matches(mode: SurfaceMode, pp: &PastPerformance) -> bool {
+    let s = pp.surface.trim().to_uppercase();
+    let all_weather = pp.previous_all_weather_surface_indicator.trim().eq_ignore_ascii_case("A");
     match mode {
-        SurfaceMode::Dirt => s == "D",
+        SurfaceMode::Dirt => s == "D" && !all_weather,
         SurfaceMode::Turf => s == "T",
+        SurfaceMode::Synthetic => all_weather,
+    }
+}
+
+fn best_speed_on_surface(h: &Horse, mode: SurfaceMode) -> Option<u32> {
+    match mode {
+        SurfaceMode::Dirt => h.best_bris_speed_fast_track,
+        SurfaceMode::Turf => h.best_bris_speed_turf,
+        SurfaceMode::Synthetic => h.best_bris_speed_all_weather_surface,
+    }
+}
+
+fn surface_record(h: &Horse, mode: SurfaceMode) -> (Option<u32>, Option<u32>) {
+    match mode {
+        SurfaceMode::Dirt => (None, None),
+        SurfaceMode::Turf => (h.lifetime_record_turf_starts, h.lifetime_record_turf_wins),
+        SurfaceMode::Synthetic => (h.lifetime_all_weather_starts, h.lifetime_all_weather_wins),
     }
 }
 
@@ -177,10 +329,47 @@ fn pick_early_fig(pp: &PastPerformance, dist_f: f64) -> Option<f64> {
     }
 }
 
-pub fn representative_figures(h: &Horse, race: &Race, mode: SurfaceMode) -> RepFigs {
-    let dist_f = yards_to_furlongs(race.distance);
-    let weights: [f64; 5] = [0.55, 0.30, 0.15, 0.08, 0.05];
+pub fn adjust_speed_figure(
+    pp: &PastPerformance,
+    mode: SurfaceMode,
+    adjustments: &FigureAdjustmentConfig,
+) -> Option<AdjustedFigure> {
+    let raw = pp.bris_speed_rating? as f64;
+
+    let variant = pp
+        .track_variant
+        .map(|v| (v as f64 - adjustments.variant_baseline) * adjustments.variant_factor)
+        .unwrap_or(0.0);
+
+    let mut condition = 0.0;
+    if is_wet_condition(&pp.track_condition) {
+        condition += adjustments.wet_track_points;
+    }
+    if is_sealed(pp) {
+        condition += adjustments.sealed_track_points;
+    }
 
+    let track_class = adjustments
+        .track_class
+        .get(&pp.track_code.trim().to_uppercase())
+        .copied()
+        .unwrap_or(0.0);
+
+    let surface = if surface_matches(mode, pp) { 0.0 } else { adjustments.off_surface_points };
+
+    Some(AdjustedFigure {
+        raw,
+        variant,
+        condition,
+        track_class,
+        surface,
+        adjusted: raw + variant + condition + track_class + surface,
+    })
+}
+
+pub fn representative_figures(h: &Horse, race: &Race, mode: SurfaceMode, config: &ModelConfig) -> RepFigs {
+    let dist_f = yards_to_furlongs(race.distance);
+    let weights = &config.recency_weights;
 
     let usable: Vec<&PastPerformance> = h
         .past_performances
@@ -191,26 +380,34 @@ pub fn representative_figures(h: &Horse, race: &Race, mode: SurfaceMode) -> RepF
     let same_surface: Vec<&PastPerformance> = usable
         .iter()
         .copied()
-        .filter(|pp| surface_matches(mode, &pp.surface))
+        .filter(|pp| surface_matches(mode, pp))
         .collect();
 
-    let pool: Vec<&PastPerformance> = if same_surface.len() >= 2 {
+    let pool: Vec<&PastPerformance> = if same_surface.len() >= config.min_same_surface_pps {
         same_surface
     } else {
         usable
     }
     .into_iter()
-    .take(5)
+    .take(weights.len())
     .collect();
 
-    let speeds: Vec<Option<f64>> = pool.iter().map(|pp| pp.bris_speed_rating.map(|x| x as f64)).collect();
+    let raw_speeds: Vec<Option<f64>> = pool.iter().map(|pp| pp.bris_speed_rating.map(|x| x as f64)).collect();
+    let speeds: Vec<Option<f64>> = match &config.figure_adjustments {
+        Some(adjustments) => pool
+            .iter()
+            .map(|pp| adjust_speed_figure(pp, mode, adjustments).map(|f| f.adjusted))
+            .collect(),
+        None => raw_speeds.clone(),
+    };
     let earlies: Vec<Option<f64>> = pool.iter().map(|pp| pick_early_fig(pp, dist_f)).collect();
     let lates: Vec<Option<f64>> = pool.iter().map(|pp| pp.bris_late_pace.map(|x| x as f64)).collect();
 
     RepFigs {
-        rep_speed: weighted_avg(&speeds, &weights),
-        rep_early: weighted_avg(&earlies, &weights),
-        rep_late: weighted_avg(&lates, &weights),
+        rep_speed: weighted_avg(&speeds, weights),
+        rep_early: weighted_avg(&earlies, weights),
+        rep_late: weighted_avg(&lates, weights),
+        raw_rep_speed: weighted_avg(&raw_speeds, weights),
     }
 }
 
@@ -237,7 +434,17 @@ fn get_pars_for_race(race: &Race, dist_f: f64) -> Option<Pars> {
     }
 }
 
-pub fn race_shape_dirt(race: &Race) -> (Shape, u32, f64) {
+fn is_pace_heat(surface: &SurfaceModelConfig, rs: RunStyle, q: u32) -> bool {
+    let ep_heat = surface
+        .pace_heat
+        .min_quirin_ep
+        .map(|min| rs == RunStyle::EP && q >= min)
+        .unwrap_or(false);
+    rs == RunStyle::E || ep_heat || q >= surface.pace_heat.min_quirin
+}
+
+fn race_shape(race: &Race, mode: SurfaceMode, config: &ModelConfig) -> (Shape, u32, f64) {
+    let surface = config.surface(mode);
     let dist_f = yards_to_furlongs(race.distance);
     let pars = get_pars_for_race(race, dist_f);
 
@@ -255,14 +462,14 @@ pub fn race_shape_dirt(race: &Race) -> (Shape, u32, f64) {
         .filter(|h| {
             let rs = parse_run_style(&h.bris_run_style);
             let q = h.quirin_speed_points.unwrap_or(0);
-            rs == RunStyle::E || q >= 6
+            is_pace_heat(surface, rs, q)
         })
         .count() as u32;
 
     let mut sum_early = 0.0;
     let mut n = 0.0;
     for h in horses {
-        let rep = representative_figures(h, race, SurfaceMode::Dirt);
+        let rep = representative_figures(h, race, mode, config);
         if let Some(e) = rep.rep_early {
             sum_early += e;
             n += 1.0;
@@ -271,78 +478,83 @@ pub fn race_shape_dirt(race: &Race) -> (Shape, u32, f64) {
     let avg_early = if n > 0.0 { sum_early / n } else { 0.0 };
     let early_vs_par = pars.map(|p| avg_early - p.early).unwrap_or(0.0);
 
-    let epi = clamp((pace_heat as f64 / field_size) * 0.70 + clamp((early_vs_par + 6.0) / 12.0, 0.0, 1.0) * 0.30, 0.0, 1.0);
+    let epi = clamp(
+        (pace_heat as f64 / field_size) * surface.epi.heat_weight
+            + clamp((early_vs_par + surface.epi.early_offset) / surface.epi.early_range, 0.0, 1.0) * surface.epi.early_weight,
+        0.0,
+        1.0,
+    );
 
-    let shape = if epi >= 0.88 {
-        Shape::Meltdown
-    } else if epi >= 0.75 {
-        Shape::Fast
-    } else if epi <= 0.25 {
-        Shape::Slow
-    } else {
-        Shape::Honest
-    };
+    let shape = surface.shape_cutoffs.shape_for(epi);
 
     (shape, pace_heat, epi)
 }
 
-pub fn race_shape_turf(race: &Race) -> (Shape, u32, f64) {
-    let dist_f = yards_to_furlongs(race.distance);
-    let pars = get_pars_for_race(race, dist_f);
+pub fn race_shape_dirt(race: &Race, config: &ModelConfig) -> (Shape, u32, f64) {
+    race_shape(race, SurfaceMode::Dirt, config)
+}
 
-    let horses: Vec<&Horse> = race
-        .horses
-        .iter()
-        .enumerate()
-        .filter(|(_, horse)| !horse.scratched)
-        .map(|(_, h)| h)
-        .collect();
-    let field_size = horses.len().max(1) as f64;
+pub fn race_shape_turf(race: &Race, config: &ModelConfig) -> (Shape, u32, f64) {
+    race_shape(race, SurfaceMode::Turf, config)
+}
 
-    let pace_heat = horses
-        .iter()
-        .filter(|h| {
-            let rs = parse_run_style(&h.bris_run_style);
-            let q = h.quirin_speed_points.unwrap_or(0);
-            rs == RunStyle::E || (rs == RunStyle::EP && q >= 5) || q >= 7
-        })
-        .count() as u32;
+pub fn race_shape_synthetic(race: &Race, config: &ModelConfig) -> (Shape, u32, f64) {
+    race_shape(race, SurfaceMode::Synthetic, config)
+}
 
-    let mut sum_early = 0.0;
-    let mut n = 0.0;
-    for h in horses {
-        let rep = representative_figures(h, race, SurfaceMode::Turf);
-        if let Some(e) = rep.rep_early {
-            sum_early += e;
-            n += 1.0;
-        }
+fn score_components(
+    h: &Horse,
+    surface: &SurfaceModelConfig,
+    layoff_penalty: Option<LayoffPenalty>,
+    shape: Shape,
+    spd: f64,
+    early: f64,
+    late: f64,
+) -> ScoreBreakdown {
+    let w = surface.weights.for_shape(shape);
+    let mut breakdown = ScoreBreakdown::default();
+
+    breakdown.push("speed_vs_par", Some(spd), Some(w.speed), w.speed * spd);
+    breakdown.push("early_vs_par", Some(early), Some(w.early), w.early * early);
+    breakdown.push("late_vs_par", Some(late), Some(w.late), w.late * late);
+
+    if let Some(pp) = h.bris_prime_power_rating {
+        breakdown.push("prime_power", Some(pp), None, (pp - 100.0) / surface.prime_power_divisor);
     }
-    let avg_early = if n > 0.0 { sum_early / n } else { 0.0 };
-    let early_vs_par = pars.map(|p| avg_early - p.early).unwrap_or(0.0);
 
-    let epi = clamp((pace_heat as f64 / field_size) * 0.60 + clamp((early_vs_par + 4.0) / 10.0, 0.0, 1.0) * 0.40, 0.0, 1.0);
+    if let (Some(penalty), Some(lay)) = (layoff_penalty, h.days_since_last_race)
+        && lay > penalty.min_days
+    {
+        let contribution = -((lay - penalty.min_days) as f64 / penalty.days_per_point).min(penalty.max_penalty);
+        breakdown.push("layoff", Some(lay as f64), None, contribution);
+    }
 
-    let shape = if epi >= 0.82 {
-        Shape::Meltdown
-    } else if epi >= 0.68 {
-        Shape::Fast
-    } else if epi <= 0.22 {
-        Shape::Slow
-    } else {
-        Shape::Honest
-    };
+    if let (Some(bonus), Some(tj)) = (surface.trainer_jockey_roi, h.trainer_jockey_combo_roi_meet) {
+        breakdown.push("trainer_jockey_roi", Some(tj), None, bonus.apply(tj));
+    }
 
-    (shape, pace_heat, epi)
+    if let (Some(bonus), Some(tp)) = (surface.turf_pedigree, parse_pedigree_rating(&h.bris_turf_pedigree_rating)) {
+        breakdown.push("turf_pedigree", Some(tp), None, bonus.apply(tp));
+    }
+
+    breakdown
 }
 
-pub fn score_horse_dirt(h: &Horse, race: &Race, shape: Shape) -> (Option<f64>, RepFigs) {
+fn score_horse_breakdown(
+    h: &Horse,
+    race: &Race,
+    shape: Shape,
+    mode: SurfaceMode,
+    config: &ModelConfig,
+) -> (Option<ScoreBreakdown>, RepFigs) {
+    let surface = config.surface(mode);
     let dist_f = yards_to_furlongs(race.distance);
     let pars = match get_pars_for_race(race, dist_f) {
         Some(p) => p,
-        None => return (None, RepFigs { rep_speed: None, rep_early: None, rep_late: None }),
+        None => return (None, RepFigs { rep_speed: None, rep_early: None, rep_late: None, raw_rep_speed: None }),
     };
 
-    let rep = representative_figures(h, race, SurfaceMode::Dirt);
+    let rep = representative_figures(h, race, mode, config);
     let (rs, re, rl) = match (rep.rep_speed, rep.rep_early, rep.rep_late) {
         (Some(a), Some(b), Some(c)) => (a, b, c),
         _ => return (None, rep),
@@ -352,75 +564,131 @@ pub fn score_horse_dirt(h: &Horse, race: &Race, shape: Shape) -> (Option<f64>, R
     let early = re - pars.early;
     let late = rl - pars.late;
 
-    let (mut w_s, mut w_e, mut w_l) = (0.45, 0.30, 0.25);
-    match shape {
-        Shape::Slow => { w_s = 0.45; w_e = 0.40; w_l = 0.15; }
-        Shape::Fast => { w_s = 0.45; w_e = 0.25; w_l = 0.30; }
-        Shape::Meltdown => { w_s = 0.40; w_e = 0.15; w_l = 0.45; }
-        Shape::Honest => {}
-    }
-
-    let mut score = w_s * spd + w_e * early + w_l * late;
+    // The form cycle scores layoff returns itself, so the flat penalty only applies without it.
+    let layoff_penalty = surface.layoff_penalty.filter(|_| config.form_cycle.is_none());
+    let mut breakdown = score_components(h, surface, layoff_penalty, shape, spd, early, late);
 
-    if let Some(pp) = h.bris_prime_power_rating {
-        score += (pp - 100.0) / 10.0;
+    if let (Some(bonus), Some(best)) = (surface.surface_best_speed, best_speed_on_surface(h, mode)) {
+        let best_vs_par = best as f64 - pars.speed;
+        breakdown.push("surface_best_speed", Some(best_vs_par), None, bonus.apply(best_vs_par));
     }
 
-    if let Some(lay) = h.days_since_last_race {
-        if lay > 60 {
-            score -= ((lay - 60) as f64 / 60.0).min(1.5);
-        }
+    if let (Some(record), (Some(starts), Some(wins))) = (surface.surface_record, surface_record(h, mode))
+        && starts >= record.min_starts
+        && starts > 0
+    {
+        let win_rate = wins as f64 / starts as f64;
+        breakdown.push("surface_record", Some(win_rate), None, record.win_rate.apply(win_rate));
     }
 
-    if let Some(tj) = h.trainer_jockey_combo_roi_meet {
-        score += clamp(tj / 4.0, -0.5, 0.5);
+    // A drop in class is a positive signal, so the bonus is applied to the negated level change.
+    if let (Some(bonus), Some(change)) = (config.class_change, class_change(h, race)) {
+        breakdown.push("class_change", Some(change.level_change), None, bonus.apply(-change.level_change));
     }
 
-    (Some(score), rep)
+    (Some(breakdown), rep)
+}
+
+pub(crate) fn parse_pedigree_rating(raw: &str) -> Option<f64> {
+    raw.replace('*', "").trim().parse::<f64>().ok()
+}
+
+fn is_first_time_starter_stat(category: &str) -> bool {
+    let c = category.trim().to_lowercase();
+    c.contains("1st time str") || c.contains("first time starter") || c.contains("debut")
+}
+
+pub fn is_lightly_raced(h: &Horse, debut: &DebutModelConfig) -> bool {
+    h.past_performances.len() <= debut.max_starts
 }
 
-pub fn score_horse_turf(h: &Horse, race: &Race, shape: Shape) -> (Option<f64>, RepFigs) {
+fn debut_score_breakdown(
+    h: &Horse,
+    race: &Race,
+    mode: SurfaceMode,
+    rep: &RepFigs,
+    workout: &WorkoutSig,
+    debut: &DebutModelConfig,
+) -> Option<ScoreBreakdown> {
     let dist_f = yards_to_furlongs(race.distance);
-    let pars = match get_pars_for_race(race, dist_f) {
-        Some(p) => p,
-        None => return (None, RepFigs { rep_speed: None, rep_early: None, rep_late: None }),
-    };
+    let pars = get_pars_for_race(race, dist_f)?;
+
+    let mut breakdown = ScoreBreakdown::default();
+    breakdown.push("debut_base", None, None, debut.base);
+
+    let partials = [
+        ("speed_vs_par", rep.rep_speed, pars.speed),
+        ("early_vs_par", rep.rep_early, pars.early),
+        ("late_vs_par", rep.rep_late, pars.late),
+    ];
+    for (name, fig, par) in partials {
+        if let Some(fig) = fig {
+            let raw = fig - par;
+            let weight = debut.partial_figure_weight / 3.0;
+            breakdown.push(name, Some(raw), Some(weight), raw * weight);
+        }
+    }
 
-    let rep = representative_figures(h, race, SurfaceMode::Turf);
-    let (rs, re, rl) = match (rep.rep_speed, rep.rep_early, rep.rep_late) {
-        (Some(a), Some(b), Some(c)) => (a, b, c),
-        _ => return (None, rep),
-    };
+    breakdown.push("workouts", Some(workout.score), None, debut.workout.apply(workout.score));
 
-    let spd = rs - pars.speed;
-    let early = re - pars.early;
-    let late = rl - pars.late;
+    if let Some(fee) = h.sire_stud_fee.filter(|f| *f > 0) {
+        let log_fee = (fee as f64).ln();
+        breakdown.push("sire_stud_fee", Some(fee as f64), None, debut.log_stud_fee.apply(log_fee));
+    }
 
-    let (mut w_s, mut w_e, mut w_l) = (0.40, 0.20, 0.40);
-    match shape {
-        Shape::Slow => { w_s = 0.45; w_e = 0.25; w_l = 0.30; }
-        Shape::Fast => { w_s = 0.40; w_e = 0.15; w_l = 0.45; }
-        Shape::Meltdown => { w_s = 0.35; w_e = 0.10; w_l = 0.55; }
-        Shape::Honest => {}
+    let pedigree = match mode {
+        SurfaceMode::Turf => parse_pedigree_rating(&h.bris_turf_pedigree_rating),
+        SurfaceMode::Dirt | SurfaceMode::Synthetic => parse_pedigree_rating(&h.bris_dirt_pedigree_rating),
+    };
+    if let Some(ped) = pedigree {
+        breakdown.push("surface_pedigree", Some(ped), None, debut.pedigree.apply(ped));
     }
 
-    let mut score = w_s * spd + w_e * early + w_l * late;
+    if let Some(price) = h.auction_price.filter(|p| *p > 0) {
+        let log_price = (price as f64).ln();
+        breakdown.push("auction_price", Some(price as f64), None, debut.log_auction_price.apply(log_price));
+    }
 
-    if let Some(pp) = h.bris_prime_power_rating {
-        score += (pp - 100.0) / 12.0;
+    let first_time_stat = h
+        .key_trainer_stats
+        .iter()
+        .filter(|k| is_first_time_starter_stat(&k.category))
+        .find(|k| k.starts.unwrap_or(0) >= debut.first_time_starter_min_starts);
+    if h.past_performances.is_empty()
+        && let Some(win_pct) = first_time_stat.and_then(|k| k.win_pct)
+    {
+        breakdown.push(
+            "trainer_first_time_starters",
+            Some(win_pct),
+            None,
+            debut.first_time_starter_win_pct.apply(win_pct),
+        );
     }
 
-    let turf_ped = h
-        .bris_turf_pedigree_rating
-        .replace('*', "")
-        .trim()
-        .parse::<f64>()
-        .ok();
-    if let Some(tp) = turf_ped {
-        score += clamp((tp - 100.0) / 30.0, -0.5, 0.8);
+    let field_size = race.horses.iter().filter(|r| !r.scratched).count().max(1) as f64;
+    if let Some(ml) = h.morning_line_odds.filter(|ml| *ml >= 0.0) {
+        let edge = 1.0 / (ml + 1.0) - 1.0 / field_size;
+        breakdown.push("morning_line", Some(ml), None, debut.morning_line_edge.apply(edge));
     }
 
-    (Some(score), rep)
+    Some(breakdown)
+}
+
+fn score_horse(h: &Horse, race: &Race, shape: Shape, mode: SurfaceMode, config: &ModelConfig) -> (Option<f64>, RepFigs) {
+    let (breakdown, rep) = score_horse_breakdown(h, race, shape, mode, config);
+    (breakdown.map(|b| b.total), rep)
+}
+
+pub fn score_horse_dirt(h: &Horse, race: &Race, shape: Shape, config: &ModelConfig) -> (Option<f64>, RepFigs) {
+    score_horse(h, race, shape, SurfaceMode::Dirt, config)
+}
+
+pub fn score_horse_turf(h: &Horse, race: &Race, shape: Shape, config: &ModelConfig) -> (Option<f64>, RepFigs) {
+    score_horse(h, race, shape, SurfaceMode::Turf, config)
+}
+
+pub fn score_horse_synthetic(h: &Horse, race: &Race, shape: Shape, config: &ModelConfig) -> (Option<f64>, RepFigs) {
+    score_horse(h, race, shape, SurfaceMode::Synthetic, config)
 }
 
 pub fn workout_signal(h: &Horse, race_date_mmddyyyy: Option<&str>, days_window: i64) -> WorkoutSig {
@@ -438,13 +706,13 @@ pub fn workout_signal(h: &Horse, race_date_mmddyyyy: Option<&str>, days_window:
         }
 
         let (rank, n) = (w.rank, w.workouts_that_day_distance);
-        if let (Some(r), Some(total)) = (rank, n) {
-            if total > 0 {
-                recent_works += 1;
-                let pct = (r as f64) / (total as f64);
-                if pct <= 0.10 {
-                    top_rank_works += 1;
-                }
+        if let (Some(r), Some(total)) = (rank, n)
+            && total > 0
+        {
+            recent_works += 1;
+            let pct = (r as f64) / (total as f64);
+            if pct <= 0.10 {
+                top_rank_works += 1;
             }
         }
     }
@@ -454,56 +722,151 @@ pub fn workout_signal(h: &Horse, race_date_mmddyyyy: Option<&str>, days_window:
     WorkoutSig { recent_works, top_rank_works, score }
 }
 
-pub fn rank_race_dirt(race: &Race, racecard_date: Option<&str>) -> RaceRankResult {
-    let dist_f = yards_to_furlongs(race.distance);
-    let (shape, pace_heat, epi) = race_shape_dirt(race);
+pub fn win_probabilities(scores: &[f64], temperature: f64) -> Vec<f64> {
+    let t = temperature.max(1e-6);
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = scores.iter().map(|s| ((s - max) / t).exp()).collect();
+    let total: f64 = weights.iter().sum();
 
-    let mut horses: Vec<HorseRank> = race
+    weights.iter().map(|w| if total > 0.0 { w / total } else { 0.0 }).collect()
+}
+
+// Runners that could not be scored still run, so each one keeps the share an
+// average scored runner would get. Returns probabilities for `scores` only.
+pub fn win_probabilities_with_unscored(scores: &[f64], unscored: usize, temperature: f64) -> Vec<f64> {
+    if scores.is_empty() || unscored == 0 {
+        return win_probabilities(scores, temperature);
+    }
+
+    let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+    let mut field = scores.to_vec();
+    field.extend(std::iter::repeat_n(mean, unscored));
+
+    let mut probabilities = win_probabilities(&field, temperature);
+    probabilities.truncate(scores.len());
+    probabilities
+}
+
+pub fn fair_odds(win_probability: f64) -> Option<f64> {
+    if win_probability > 0.0 {
+        Some((1.0 - win_probability) / win_probability)
+    } else {
+        None
+    }
+}
+
+fn assign_win_probabilities(race: &Race, horses: &mut [HorseRank], temperature: f64) {
+    let scratched: HashSet<&str> = race
         .horses
         .iter()
-        .map(|h| {
-            let run_style = parse_run_style(&h.bris_run_style);
-            let (score, rep) = score_horse_dirt(h, race, shape);
-            let workout = workout_signal(h, racecard_date, 21);
+        .filter(|h| h.scratched)
+        .map(|h| h.program_number.as_str())
+        .collect();
 
-            HorseRank {
-                program_number: h.program_number.clone(),
-                horse_name: h.horse_name.clone(),
-                post_position: h.post_position,
-                run_style,
-                quirin: h.quirin_speed_points,
-                shape,
-                score,
-                rep,
-                workout,
-            }
+    let eligible: Vec<usize> = horses
+        .iter()
+        .enumerate()
+        .filter(|(_, rank)| {
+            !scratched.contains(rank.program_number.as_str()) && rank.score.map(|s| s.is_finite()).unwrap_or(false)
         })
+        .map(|(i, _)| i)
         .collect();
 
-    horses.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-
-    RaceRankResult {
-        race_number: race.race_number,
-        surface_mode: SurfaceMode::Dirt,
-        distance_f: dist_f,
-        shape,
-        pace_heat,
-        epi,
-        horses,
+    let runners = horses
+        .iter()
+        .filter(|rank| !scratched.contains(rank.program_number.as_str()))
+        .count();
+    let scores: Vec<f64> = eligible.iter().filter_map(|&i| horses[i].score).collect();
+    let probabilities = win_probabilities_with_unscored(&scores, runners - eligible.len(), temperature);
+
+    for h in horses.iter_mut() {
+        h.win_probability = None;
+        h.fair_odds = None;
+    }
+    for (&i, p) in eligible.iter().zip(probabilities) {
+        horses[i].win_probability = Some(p);
+        horses[i].fair_odds = fair_odds(p);
     }
 }
 
-pub fn rank_race_turf(race: &Race, racecard_date: Option<&str>) -> RaceRankResult {
+fn rank_race(
+    race: &Race,
+    ctx: &RankContext,
+    mode: SurfaceMode,
+    config: &ModelConfig,
+) -> RaceRankResult {
     let dist_f = yards_to_furlongs(race.distance);
-    let (shape, pace_heat, epi) = race_shape_turf(race);
+    let (shape, pace_heat, epi) = race_shape(race, mode, config);
 
     let mut horses: Vec<HorseRank> = race
         .horses
         .iter()
         .map(|h| {
             let run_style = parse_run_style(&h.bris_run_style);
-            let (score, rep) = score_horse_turf(h, race, shape);
-            let workout = workout_signal(h, racecard_date, 21);
+            let (breakdown, rep) = score_horse_breakdown(h, race, shape, mode, config);
+            let workout = workout_signal(h, ctx.racecard_date, config.workout_days_window);
+
+            let (mut breakdown, low_confidence) = match breakdown {
+                Some(b) => (Some(b), false),
+                None => match &config.debut {
+                    Some(debut_config) if is_lightly_raced(h, debut_config) => {
+                        let debut = debut_score_breakdown(h, race, mode, &rep, &workout, debut_config);
+                        let low_confidence = debut.is_some();
+                        (debut, low_confidence)
+                    }
+                    _ => (None, false),
+                },
+            };
+
+            let equipment_events = decode_equipment_events(h);
+            let angles = config
+                .trainer_angles
+                .map(|angle_config| matched_trainer_angles(h, race, &equipment_events, ctx.track_code, &angle_config))
+                .unwrap_or_default();
+            // The debut model already scores the trainer's first-time-starter stat.
+            if !low_confidence
+                && let (Some(b), Some(angle_score)) = (breakdown.as_mut(), angles.score)
+            {
+                b.push("trainer_angles", Some(angles.angles.len() as f64), None, angle_score);
+            }
+
+            if !low_confidence
+                && let (Some(b), Some(equipment)) = (breakdown.as_mut(), config.equipment)
+            {
+                let covered_by_angle = |event: &EquipmentEvent| {
+                    angles.score.is_some() && angles.angles.iter().any(|a| angle_equipment_events(a.kind).contains(event))
+                };
+                for event in equipment_events.iter().filter(|e| !covered_by_angle(e)) {
+                    b.push(event.name(), None, None, equipment.points(*event));
+                }
+            }
+
+            let form_cycle = config.form_cycle.map(|form_config| analyze_form_cycle(h, &workout, &form_config));
+            if let (Some(b), Some(form)) = (breakdown.as_mut(), form_cycle.as_ref())
+                && form.state != FormState::NoForm
+            {
+                b.push("form_cycle", form.last_figure.map(|f| f as f64), None, form.adjustment);
+            }
+
+            let switches = config
+                .pedigree_switch
+                .map(|switch_config| analyze_switches(h, race, mode, ctx.sire_stats, &switch_config))
+                .unwrap_or_default();
+            if let Some(b) = breakdown.as_mut() {
+                for switch in &switches {
+                    b.push(switch.kind.name(), switch.pedigree_rating, None, switch.score);
+                }
+            }
+
+            let connections = config
+                .connections
+                .map(|connections_config| analyze_connections(h, race, ctx.racecard, &connections_config))
+                .unwrap_or_default();
+            if let Some(b) = breakdown.as_mut() {
+                for factor in &connections {
+                    b.push(factor.kind.name(), factor.win_pct_delta, None, factor.contribution);
+                }
+            }
 
             HorseRank {
                 program_number: h.program_number.clone(),
@@ -512,18 +875,29 @@ pub fn rank_race_turf(race: &Race, racecard_date: Option<&str>) -> RaceRankResul
                 run_style,
                 quirin: h.quirin_speed_points,
                 shape,
-                score,
+                score: breakdown.as_ref().map(|b| b.total),
                 rep,
                 workout,
+                breakdown,
+                low_confidence,
+                win_probability: None,
+                fair_odds: None,
+                angle_score: angles.score,
+                trainer_angles: angles.angles.into_iter().map(|a| a.category).collect(),
+                equipment_events,
+                form_cycle,
+                switches,
+                connections,
             }
         })
         .collect();
 
+    assign_win_probabilities(race, &mut horses, config.win_probability_temperature);
     horses.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
 
     RaceRankResult {
         race_number: race.race_number,
-        surface_mode: SurfaceMode::Turf,
+        surface_mode: mode,
         distance_f: dist_f,
         shape,
         pace_heat,
@@ -532,19 +906,47 @@ pub fn rank_race_turf(race: &Race, racecard_date: Option<&str>) -> RaceRankResul
     }
 }
 
-pub fn rank_race_auto(race: &Race, racecard_date: Option<&str>) -> RaceRankResult {
-    let surf = race.surface.trim().to_uppercase();
-    if surf == "T" {
-        rank_race_turf(race, racecard_date)
+pub fn rank_race_dirt(race: &Race, racecard_date: Option<&str>, config: &ModelConfig) -> RaceRankResult {
+    rank_race(race, &RankContext::new(racecard_date), SurfaceMode::Dirt, config)
+}
+
+pub fn rank_race_turf(race: &Race, racecard_date: Option<&str>, config: &ModelConfig) -> RaceRankResult {
+    rank_race(race, &RankContext::new(racecard_date), SurfaceMode::Turf, config)
+}
+
+pub fn rank_race_synthetic(race: &Race, racecard_date: Option<&str>, config: &ModelConfig) -> RaceRankResult {
+    rank_race(race, &RankContext::new(racecard_date), SurfaceMode::Synthetic, config)
+}
+
+pub fn race_surface_mode(race: &Race) -> SurfaceMode {
+    if race.surface.trim().eq_ignore_ascii_case("T") {
+        SurfaceMode::Turf
+    } else if race.all_weather_surface_flag.trim().eq_ignore_ascii_case("A") {
+        SurfaceMode::Synthetic
     } else {
-        rank_race_dirt(race, racecard_date)
+        SurfaceMode::Dirt
     }
 }
 
+pub fn rank_race_auto(
+    race: &Race,
+    racecard_date: Option<&str>,
+    track_code: Option<&str>,
+    config: &ModelConfig,
+) -> RaceRankResult {
+    rank_race_with_context(race, &RankContext { racecard_date, track_code, ..RankContext::default() }, config)
+}
+
+pub fn rank_race_with_context(race: &Race, ctx: &RankContext, config: &ModelConfig) -> RaceRankResult {
+    rank_race(race, ctx, race_surface_mode(race), config)
+}
+
+// Debut-model scores are on a rougher scale, so they never set the race's gaps.
 fn sorted_scores(race: &RaceRankResult) -> Vec<f64> {
     let mut scores: Vec<f64> = race
         .horses
         .iter()
+        .filter(|h| !h.low_confidence)
         .filter_map(|h| h.score)
         .filter(|s| s.is_finite())
         .collect();
@@ -554,7 +956,7 @@ fn sorted_scores(race: &RaceRankResult) -> Vec<f64> {
 
 fn top_two_scores(race: &RaceRankResult) -> (Option<f64>, Option<f64>) {
     let scores = sorted_scores(race);
-    let top = scores.get(0).copied();
+    let top = scores.first().copied();
     let second = scores.get(1).copied();
     (top, second)
 }
@@ -571,7 +973,8 @@ pub fn top_n_horses_by_score(race: &RaceRankResult, n: usize) -> Vec<HorseRank>
     horses
 }
 
-pub fn classify_race(race: &RaceRankResult) -> Confidence {
+pub fn classify_race(race: &RaceRankResult, config: &ModelConfig) -> Confidence {
+    let t = &config.confidence;
     let (top, second) = top_two_scores(race);
     if top.is_none() || second.is_none() {
         return Confidence::Unscorable;
@@ -585,11 +988,11 @@ pub fn classify_race(race: &RaceRankResult) -> Confidence {
         None
     };
 
-    if gap >= 2.0 && spread.unwrap_or(0.0) >= 3.0 {
+    if gap >= t.strong_single_gap && spread.unwrap_or(0.0) >= t.strong_single_spread {
         Confidence::StrongSingle
-    } else if gap >= 1.5 {
+    } else if gap >= t.playable_gap {
         Confidence::Playable
-    } else if gap >= 0.5 || spread.map(|s| s >= 1.5).unwrap_or(false) {
+    } else if gap >= t.competitive_gap || spread.map(|s| s >= t.competitive_spread).unwrap_or(false) {
         Confidence::Competitive
     } else {
         Confidence::WideOpen
@@ -602,13 +1005,18 @@ pub fn win_bet_suggestion(
 ) -> Option<WinBetSuggestion> {
     let opts = opts.unwrap_or_default();
 
-    let top2 = top_n_horses_by_score(race, 2);
+    let mut top2: Vec<&HorseRank> = race
+        .horses
+        .iter()
+        .filter(|h| !h.low_confidence && h.score.map(|s| s.is_finite()).unwrap_or(false))
+        .collect();
+    top2.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
     if top2.len() < 2 {
         return None;
     }
 
-    let top = &top2[0];
-    let second = &top2[1];
+    let top = top2[0];
+    let second = top2[1];
 
     let top_score = top.score?;
     let gap = top.score? - second.score?;
@@ -628,8 +1036,17 @@ pub fn win_bet_suggestion(
     }
 }
 
-pub fn derive_race_meta(race: &Race, racecard_date: Option<&str>) -> RaceMeta {
-    let race_rank_result = rank_race_auto(race, racecard_date);
+pub fn derive_race_meta(
+    race: &Race,
+    racecard_date: Option<&str>,
+    track_code: Option<&str>,
+    config: &ModelConfig,
+) -> RaceMeta {
+    derive_race_meta_with_context(race, &RankContext { racecard_date, track_code, ..RankContext::default() }, config)
+}
+
+pub fn derive_race_meta_with_context(race: &Race, ctx: &RankContext, config: &ModelConfig) -> RaceMeta {
+    let race_rank_result = rank_race_with_context(race, ctx, config);
     let mut calc_rank_result = race_rank_result.clone();
     
     let scratched_horses: HashSet<String> = race.horses
@@ -656,8 +1073,8 @@ pub fn derive_race_meta(race: &Race, racecard_date: Option<&str>) -> RaceMeta {
         None
     };
 
-    let confidence = classify_race(&calc_rank_result);
-    let win_bet = win_bet_suggestion(&calc_rank_result, None);
+    let confidence = classify_race(&calc_rank_result, config);
+    let win_bet = win_bet_suggestion(&calc_rank_result, Some(config.win_bet));
 
     RaceMeta {
         race_number: race_rank_result.race_number,
@@ -672,3 +1089,254 @@ pub fn derive_race_meta(race: &Race, racecard_date: Option<&str>) -> RaceMeta {
         race_rank_result
     }
 }
+
+fn is_main_track_only(h: &Horse) -> bool {
+    h.main_track_only_ae_indicator.trim().eq_ignore_ascii_case("M")
+}
+
+fn is_wet_condition(track_condition: &str) -> bool {
+    matches!(
+        track_condition.trim().to_lowercase().as_str(),
+        "sy" | "sly" | "my" | "sl" | "wf" | "hy" | "sys" | "mys"
+    )
+}
+
+fn is_sealed(pp: &PastPerformance) -> bool {
+    pp.sealed_track_indicator.trim().eq_ignore_ascii_case("s")
+}
+
+fn wet_adjustment(h: &Horse, rep: &RepFigs, sealed: bool, config: &ModelConfig) -> f64 {
+    let wet = &config.wet_track;
+    let mut adjustment = 0.0;
+
+    let wet_lines: Vec<&PastPerformance> = h
+        .past_performances
+        .iter()
+        .filter(|pp| !pp.surface.trim().eq_ignore_ascii_case("T") && is_wet_condition(&pp.track_condition))
+        .collect();
+    // A sealed wet track plays differently from an open one, so prefer lines run over the same kind of surface.
+    let matching: Vec<&PastPerformance> = wet_lines.iter().copied().filter(|pp| is_sealed(pp) == sealed).collect();
+    let form_lines = if matching.is_empty() { wet_lines } else { matching };
+    let wet_speeds: Vec<Option<f64>> = form_lines
+        .iter()
+        .take(config.recency_weights.len())
+        .map(|pp| pp.bris_speed_rating.map(|x| x as f64))
+        .collect();
+    if let (Some(wet_speed), Some(rep_speed)) = (weighted_avg(&wet_speeds, &config.recency_weights), rep.rep_speed) {
+        adjustment += wet.wet_form.apply(wet_speed - rep_speed);
+    }
+
+    if let (Some(starts), Some(wins)) = (h.lifetime_record_wet_starts, h.lifetime_record_wet_wins)
+        && starts >= wet.wet_record.min_starts
+        && starts > 0
+    {
+        adjustment += wet.wet_record.win_rate.apply(wins as f64 / starts as f64);
+    }
+
+    if let Some(mp) = parse_pedigree_rating(&h.bris_mud_pedigree_rating) {
+        adjustment += wet.mud_pedigree.apply(mp);
+    }
+
+    adjustment
+}
+
+fn runner_ranks(race: &RaceRankResult) -> Vec<(String, u32)> {
+    race.horses
+        .iter()
+        .filter(|h| h.win_probability.is_some())
+        .enumerate()
+        .map(|(i, h)| (h.program_number.clone(), i as u32 + 1))
+        .collect()
+}
+
+pub fn rank_race_with_conditions(
+    race: &Race,
+    ctx: &RankContext,
+    scenario: TrackScenario,
+    config: &ModelConfig,
+) -> ScenarioRankResult {
+    let on_turf = race_surface_mode(race) == SurfaceMode::Turf;
+
+    let mut fast_race = race.clone();
+    if on_turf {
+        for h in fast_race.horses.iter_mut().filter(|h| is_main_track_only(h)) {
+            h.scratched = true;
+        }
+    }
+    let fast = rank_race_with_context(&fast_race, ctx, config);
+
+    let mut projected_race = if scenario.off_turf { race.clone() } else { fast_race.clone() };
+    let mode = if scenario.off_turf && on_turf {
+        projected_race.surface = "D".to_string();
+        race_surface_mode(&projected_race)
+    } else {
+        race_surface_mode(race)
+    };
+
+    let mut projected = rank_race(&projected_race, ctx, mode, config);
+    let mut adjustments: HashMap<String, f64> = HashMap::new();
+
+    if scenario.wet_track && mode != SurfaceMode::Turf {
+        for rank in projected.horses.iter_mut() {
+            let Some(h) = projected_race.horses.iter().find(|h| h.program_number == rank.program_number) else {
+                continue;
+            };
+            let adjustment = wet_adjustment(h, &rank.rep, scenario.sealed, config);
+            if let Some(breakdown) = rank.breakdown.as_mut() {
+                breakdown.push("wet_track", None, None, adjustment);
+                rank.score = Some(breakdown.total);
+            }
+            adjustments.insert(rank.program_number.clone(), adjustment);
+        }
+
+        assign_win_probabilities(&projected_race, &mut projected.horses, config.win_probability_temperature);
+        projected
+            .horses
+            .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    let fast_ranks = runner_ranks(&fast);
+    let projected_ranks = runner_ranks(&projected);
+    let rank_of = |ranks: &[(String, u32)], program: &str| ranks.iter().find(|(p, _)| p == program).map(|(_, r)| *r);
+    let score_of = |ranked: &RaceRankResult, program: &str| {
+        ranked.horses.iter().find(|h| h.program_number == program).and_then(|h| h.score)
+    };
+
+    let changes = projected
+        .horses
+        .iter()
+        .map(|rank| {
+            let fast_rank = rank_of(&fast_ranks, &rank.program_number);
+            let projected_rank = rank_of(&projected_ranks, &rank.program_number);
+
+            ScenarioRankChange {
+                program_number: rank.program_number.clone(),
+                horse_name: rank.horse_name.clone(),
+                main_track_only: race
+                    .horses
+                    .iter()
+                    .any(|h| h.program_number == rank.program_number && is_main_track_only(h)),
+                fast_rank,
+                projected_rank,
+                rank_change: match (fast_rank, projected_rank) {
+                    (Some(f), Some(p)) => Some(f as i32 - p as i32),
+                    _ => None,
+                },
+                fast_score: score_of(&fast, &rank.program_number),
+                projected_score: rank.score,
+                wet_adjustment: adjustments.get(&rank.program_number).copied().unwrap_or(0.0),
+            }
+        })
+        .collect();
+
+    ScenarioRankResult {
+        scenario,
+        fast,
+        projected,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn past_performance(race_date: &str, speed: u32) -> PastPerformance {
+        PastPerformance {
+            race_date: race_date.to_string(),
+            track_code: "SAR".to_string(),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            bris_speed_rating: Some(speed),
+            bris_4f_pace: Some(speed),
+            bris_late_pace: Some(speed),
+            ..Default::default()
+        }
+    }
+
+    fn horse(program_number: &str, speeds: &[u32]) -> Horse {
+        Horse {
+            program_number: program_number.to_string(),
+            horse_name: format!("Horse {program_number}"),
+            past_performances: speeds
+                .iter()
+                .enumerate()
+                .map(|(i, &s)| past_performance(&format!("12/{:02}/2025", 20 - i), s))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn race(horses: Vec<Horse>) -> Race {
+        Race {
+            race_number: Some(1),
+            distance: Some(1320),
+            surface: "D".to_string(),
+            four_f_bris_pace_par: Some(85),
+            bris_speed_for_class: Some(85),
+            bris_late_pace_par: Some(85),
+            horses,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn win_probabilities_sum_to_one_and_follow_score() {
+        let p = win_probabilities(&[3.0, 1.0, 0.0], 1.0);
+        assert!((p.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(p[0] > p[1] && p[1] > p[2]);
+        assert_eq!(win_probabilities(&[], 1.0), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn unscored_runners_keep_a_share_of_the_mass() {
+        let scored = win_probabilities_with_unscored(&[2.0, 2.0], 2, 1.0);
+        assert_eq!(scored.len(), 2);
+        assert!((scored[0] - 0.25).abs() < 1e-9);
+        assert_eq!(win_probabilities_with_unscored(&[2.0, 2.0], 0, 1.0), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn fair_odds_need_a_positive_probability() {
+        assert_eq!(fair_odds(0.25), Some(3.0));
+        assert_eq!(fair_odds(0.0), None);
+    }
+
+    #[test]
+    fn ranked_race_leaves_mass_for_unscored_and_none_for_scratched() {
+        let mut scratched = horse("4", &[95, 94, 93]);
+        scratched.scratched = true;
+        let race = race(vec![horse("1", &[90, 88, 85]), horse("2", &[80, 78, 76]), horse("3", &[]), scratched]);
+
+        let result = rank_race_auto(&race, Some("20260101"), Some("SAR"), &ModelConfig::default());
+        let probability = |pn: &str| result.horses.iter().find(|h| h.program_number == pn).unwrap().win_probability;
+
+        let scored = probability("1").unwrap() + probability("2").unwrap();
+        assert!(scored < 1.0 && scored > 0.5, "scored mass {scored}");
+        assert!(probability("1") > probability("2"));
+        assert_eq!(probability("3"), None);
+        assert_eq!(probability("4"), None);
+    }
+
+    #[test]
+    fn sealed_scenarios_weigh_wet_form_over_a_sealed_surface() {
+        let mut mudder = horse("1", &[85, 85, 95, 70]);
+        for (pp, sealed) in mudder.past_performances.iter_mut().skip(2).zip(["s", ""]) {
+            pp.track_condition = "sy".to_string();
+            pp.sealed_track_indicator = sealed.to_string();
+        }
+        let race = race(vec![mudder, horse("2", &[85, 85, 85])]);
+        let adjustment = |scenario: TrackScenario| {
+            let ctx = RankContext::new(Some("20260101"));
+            let result = rank_race_with_conditions(&race, &ctx, scenario, &ModelConfig::default());
+            result.changes.iter().find(|c| c.program_number == "1").unwrap().wet_adjustment
+        };
+
+        let sealed = adjustment(TrackScenario { wet_track: true, sealed: true, ..Default::default() });
+        let open = adjustment(TrackScenario { wet_track: true, ..Default::default() });
+
+        assert!(sealed > 0.0, "sealed {sealed}");
+        assert!(open < sealed, "open {open} sealed {sealed}");
+        assert_eq!(adjustment(TrackScenario { sealed: true, ..Default::default() }), 0.0);
+    }
+}
@@ -3,5 +3,7 @@ pub mod models;
 pub mod constants;
 pub mod contextual_speed_and_pace_model;
 pub mod build_racecard;
+pub mod build_race_results;
 pub mod utils;
-pub mod sqlite;
\ No newline at end of file
+pub mod sqlite;
+pub mod store;
\ No newline at end of file
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::contextual_speed_and_pace_model::{Confidence, Shape, SurfaceMode};
+use crate::models::pedigree_switch::SireStats;
+use crate::models::race_result::RaceResult;
+use crate::models::racecard::{Race, Racecard};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestFilters {
+    pub track_code: Option<String>,
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRace {
+    pub racecard_id: i64,
+    pub track_code: String,
+    pub date: String,
+    pub race: Race,
+    pub result: RaceResult,
+    // Shared by every race on the card so ranking sees the same context as a live card.
+    #[serde(skip)]
+    pub racecard: Option<Arc<Racecard>>,
+    #[serde(skip)]
+    pub sire_stats: Arc<Vec<SireStats>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlatStakeStats {
+    pub bets: u32,
+    pub wins: u32,
+    pub hit_rate: f64,
+    pub staked: f64,
+    pub returned: f64,
+    pub profit: f64,
+    pub roi: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestStats {
+    pub races: u32,
+    pub top_pick: FlatStakeStats,
+    pub win_bet: FlatStakeStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRaceOutcome {
+    pub race_id: i64,
+    pub track_code: String,
+    pub date: String,
+    pub race_number: Option<u32>,
+    pub surface_mode: SurfaceMode,
+    pub shape: Shape,
+    pub confidence: Confidence,
+    pub top_pick: Option<String>,
+    pub win_bet: Option<String>,
+    pub winners: Vec<String>,
+    pub top_pick_payoff: Option<f64>,
+    pub win_bet_payoff: Option<f64>,
+    pub top_pick_won: bool,
+    pub win_bet_won: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub model_version: String,
+    pub params_hash: String,
+    pub stake: f64,
+    pub overall: BacktestStats,
+    pub by_confidence: BTreeMap<String, BacktestStats>,
+    pub by_shape: BTreeMap<String, BacktestStats>,
+    pub by_surface: BTreeMap<String, BacktestStats>,
+    pub by_track: BTreeMap<String, BacktestStats>,
+    pub races: Vec<BacktestRaceOutcome>,
+}
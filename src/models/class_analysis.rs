@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClassType {
+    MaidenClaiming,
+    Maiden,
+    Claiming,
+    Starter,
+    OptionalClaiming,
+    Allowance,
+    Stakes,
+    Graded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClassDirection {
+    Rise,
+    Drop,
+    Same,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceClass {
+    pub class_type: ClassType,
+    pub grade: Option<u8>,
+    pub price: Option<u32>,
+    pub condition: Option<String>,
+    pub level: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassChange {
+    pub today: RaceClass,
+    pub last: RaceClass,
+    pub level_change: f64,
+    pub claiming_price_change: Option<i64>,
+    pub purse_change: Option<i64>,
+    pub direction: ClassDirection,
+}
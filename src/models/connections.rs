@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionChangeKind {
+    JockeyUpgrade,
+    JockeyDowngrade,
+    JockeyChange,
+    FirstAfterClaim,
+    TrainerChange,
+}
+
+impl ConnectionChangeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionChangeKind::JockeyUpgrade => "jockey_upgrade",
+            ConnectionChangeKind::JockeyDowngrade => "jockey_downgrade",
+            ConnectionChangeKind::JockeyChange => "jockey_change",
+            ConnectionChangeKind::FirstAfterClaim => "first_after_claim",
+            ConnectionChangeKind::TrainerChange => "trainer_change",
+        }
+    }
+}
+
+// A PP's "claimed and trainer switches" fields: the switch date and the new trainer with
+// that trainer's starts and wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainerSwitch {
+    pub date: String,
+    pub trainer: String,
+    pub starts: Option<u32>,
+    pub wins: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionFactor {
+    pub kind: ConnectionChangeKind,
+    pub previous: String,
+    pub current: String,
+    pub previous_win_pct: Option<f64>,
+    pub current_win_pct: Option<f64>,
+    pub win_pct_delta: Option<f64>,
+    pub roi: Option<f64>,
+    pub contribution: f64,
+    pub explanation: String,
+}
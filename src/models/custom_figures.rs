@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FigurePar {
+    pub track_code: String,
+    pub surface: String,
+    pub distance: i32,
+    pub races: u32,
+    pub fraction_1: Option<f64>,
+    pub fraction_2: Option<f64>,
+    pub fraction_3: Option<f64>,
+    pub final_time: Option<f64>,
+    pub track_variant: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomFigure {
+    pub race_date: String,
+    pub track_code: String,
+    pub surface: String,
+    pub distance: i32,
+    pub first_call_time: Option<f64>,
+    pub second_call_time: Option<f64>,
+    pub final_time: Option<f64>,
+    pub first_call_figure: Option<f64>,
+    pub second_call_figure: Option<f64>,
+    pub late_pace_figure: Option<f64>,
+    pub speed_figure: Option<f64>,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquipmentEvent {
+    BlinkersOn,
+    FirstTimeBlinkers,
+    BlinkersOff,
+    FirstTimeLasix,
+    LasixOff,
+    NasalStripOn,
+    NasalStripOff,
+}
+
+impl EquipmentEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquipmentEvent::BlinkersOn => "blinkers_on",
+            EquipmentEvent::FirstTimeBlinkers => "first_time_blinkers",
+            EquipmentEvent::BlinkersOff => "blinkers_off",
+            EquipmentEvent::FirstTimeLasix => "first_time_lasix",
+            EquipmentEvent::LasixOff => "lasix_off",
+            EquipmentEvent::NasalStripOn => "nasal_strip_on",
+            EquipmentEvent::NasalStripOff => "nasal_strip_off",
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use crate::contextual_speed_and_pace_model::Confidence;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExoticBetType {
+    Exacta,
+    Trifecta,
+    Superfecta,
+}
+
+impl ExoticBetType {
+    pub fn positions(&self) -> usize {
+        match self {
+            ExoticBetType::Exacta => 2,
+            ExoticBetType::Trifecta => 3,
+            ExoticBetType::Superfecta => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TicketStructure {
+    Key,
+    Box,
+    PartWheel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExoticTicket {
+    pub bet_type: ExoticBetType,
+    pub structure: TicketStructure,
+    pub legs: Vec<Vec<String>>,
+    pub base_amount: f64,
+    pub combinations: u32,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExoticOptions {
+    pub bet_types: Vec<ExoticBetType>,
+    pub base_amount: f64,
+    pub budget: f64,
+}
+
+impl Default for ExoticOptions {
+    fn default() -> Self {
+        Self {
+            bet_types: vec![ExoticBetType::Exacta, ExoticBetType::Trifecta, ExoticBetType::Superfecta],
+            base_amount: 1.0,
+            budget: 60.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExoticPlan {
+    pub race_number: Option<u32>,
+    pub confidence: Confidence,
+    pub tickets: Vec<ExoticTicket>,
+    pub total_cost: f64,
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormState {
+    NoForm,
+    FreshReturn,
+    WorkoutBackedReturn,
+    SecondOffLayoff,
+    ThirdOffLayoff,
+    BounceCandidate,
+    Improving,
+    Declining,
+    Steady,
+}
+
+impl FormState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FormState::NoForm => "no form",
+            FormState::FreshReturn => "first off layoff",
+            FormState::WorkoutBackedReturn => "first off layoff, workout-backed",
+            FormState::SecondOffLayoff => "second off layoff",
+            FormState::ThirdOffLayoff => "third off layoff",
+            FormState::BounceCandidate => "bounce candidate",
+            FormState::Improving => "improving",
+            FormState::Declining => "declining",
+            FormState::Steady => "steady",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormCycle {
+    pub state: FormState,
+    pub label: String,
+    pub adjustment: f64,
+    pub starts_since_layoff: Option<u32>,
+    pub last_figure: Option<u32>,
+    pub previous_top: Option<u32>,
+    pub workout_backed: bool,
+}
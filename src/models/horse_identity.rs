@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorseIdentity {
+    pub id: i64,
+    pub horse_name: String,
+    pub year_of_birth: Option<u32>,
+    pub dam: String,
+    pub sire: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorseEntry {
+    pub horse_id: i64,
+    pub horse_identity_id: i64,
+    pub race_id: i64,
+    pub racecard_id: i64,
+    pub track_code: String,
+    pub track: String,
+    pub date: String,
+    pub race_number: Option<u32>,
+    pub program_number: String,
+    pub scratched: bool,
+}
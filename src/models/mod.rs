@@ -1,2 +1,21 @@
 pub mod trip_handicapping;
-pub mod racecard;
\ No newline at end of file
+pub mod racecard;
+pub mod horse_identity;
+pub mod race_result;
+pub mod race_meta_snapshot;
+pub mod trip_comment_search;
+pub mod model_config;
+pub mod backtest;
+pub mod parameter_search;
+pub mod win_probability;
+pub mod exotics;
+pub mod pick_n;
+pub mod staking;
+pub mod pace_projection;
+pub mod custom_figures;
+pub mod class_analysis;
+pub mod trainer_angles;
+pub mod equipment;
+pub mod form_cycle;
+pub mod pedigree_switch;
+pub mod connections;
\ No newline at end of file
@@ -0,0 +1,767 @@
+use std::collections::BTreeMap;
+use serde::de::Error as _;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use tokio::fs;
+use crate::contextual_speed_and_pace_model::{Shape, SurfaceMode, WinBetOptions};
+use crate::models::equipment::EquipmentEvent;
+
+// Shared by the factors that need to call a race a route or a start a layoff return.
+pub const DEFAULT_ROUTE_YARDS: i32 = 1760;
+pub const DEFAULT_LAYOFF_DAYS: u32 = 60;
+pub const DEFAULT_CLASS_CHANGE: ScaledBonus = ScaledBonus { center: 0.0, divisor: 20.0, min: -0.5, max: 0.5 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ComponentWeights {
+    pub speed: f64,
+    pub early: f64,
+    pub late: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeWeights {
+    pub slow: ComponentWeights,
+    pub honest: ComponentWeights,
+    pub fast: ComponentWeights,
+    pub meltdown: ComponentWeights,
+}
+
+impl ShapeWeights {
+    pub fn for_shape(&self, shape: Shape) -> ComponentWeights {
+        match shape {
+            Shape::Slow => self.slow,
+            Shape::Honest => self.honest,
+            Shape::Fast => self.fast,
+            Shape::Meltdown => self.meltdown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeCutoffs {
+    pub meltdown: f64,
+    pub fast: f64,
+    pub slow: f64,
+}
+
+impl ShapeCutoffs {
+    pub fn shape_for(&self, epi: f64) -> Shape {
+        if epi >= self.meltdown {
+            Shape::Meltdown
+        } else if epi >= self.fast {
+            Shape::Fast
+        } else if epi <= self.slow {
+            Shape::Slow
+        } else {
+            Shape::Honest
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EpiBlend {
+    pub heat_weight: f64,
+    pub early_weight: f64,
+    pub early_offset: f64,
+    pub early_range: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaceHeatRule {
+    pub min_quirin: u32,
+    pub min_quirin_ep: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayoffPenalty {
+    pub min_days: u32,
+    pub days_per_point: f64,
+    pub max_penalty: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScaledBonus {
+    pub center: f64,
+    pub divisor: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ScaledBonus {
+    pub fn apply(&self, value: f64) -> f64 {
+        ((value - self.center) / self.divisor).max(self.min).min(self.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceRecordBonus {
+    pub min_starts: u32,
+    pub win_rate: ScaledBonus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurfaceModelConfig {
+    pub pace_heat: PaceHeatRule,
+    pub epi: EpiBlend,
+    pub shape_cutoffs: ShapeCutoffs,
+    pub weights: ShapeWeights,
+    pub prime_power_divisor: f64,
+    pub layoff_penalty: Option<LayoffPenalty>,
+    pub trainer_jockey_roi: Option<ScaledBonus>,
+    pub turf_pedigree: Option<ScaledBonus>,
+    pub surface_best_speed: Option<ScaledBonus>,
+    pub surface_record: Option<SurfaceRecordBonus>,
+}
+
+impl Default for SurfaceModelConfig {
+    fn default() -> Self {
+        Self::dirt()
+    }
+}
+
+impl SurfaceModelConfig {
+    pub fn dirt() -> Self {
+        Self {
+            pace_heat: PaceHeatRule { min_quirin: 6, min_quirin_ep: None },
+            epi: EpiBlend { heat_weight: 0.70, early_weight: 0.30, early_offset: 6.0, early_range: 12.0 },
+            shape_cutoffs: ShapeCutoffs { meltdown: 0.88, fast: 0.75, slow: 0.25 },
+            weights: ShapeWeights {
+                slow: ComponentWeights { speed: 0.45, early: 0.40, late: 0.15 },
+                honest: ComponentWeights { speed: 0.45, early: 0.30, late: 0.25 },
+                fast: ComponentWeights { speed: 0.45, early: 0.25, late: 0.30 },
+                meltdown: ComponentWeights { speed: 0.40, early: 0.15, late: 0.45 },
+            },
+            prime_power_divisor: 10.0,
+            layoff_penalty: Some(LayoffPenalty { min_days: 60, days_per_point: 60.0, max_penalty: 1.5 }),
+            trainer_jockey_roi: Some(ScaledBonus { center: 0.0, divisor: 4.0, min: -0.5, max: 0.5 }),
+            turf_pedigree: None,
+            surface_best_speed: None,
+            surface_record: None,
+        }
+    }
+
+    pub fn turf() -> Self {
+        Self {
+            pace_heat: PaceHeatRule { min_quirin: 7, min_quirin_ep: Some(5) },
+            epi: EpiBlend { heat_weight: 0.60, early_weight: 0.40, early_offset: 4.0, early_range: 10.0 },
+            shape_cutoffs: ShapeCutoffs { meltdown: 0.82, fast: 0.68, slow: 0.22 },
+            weights: ShapeWeights {
+                slow: ComponentWeights { speed: 0.45, early: 0.25, late: 0.30 },
+                honest: ComponentWeights { speed: 0.40, early: 0.20, late: 0.40 },
+                fast: ComponentWeights { speed: 0.40, early: 0.15, late: 0.45 },
+                meltdown: ComponentWeights { speed: 0.35, early: 0.10, late: 0.55 },
+            },
+            prime_power_divisor: 12.0,
+            layoff_penalty: None,
+            trainer_jockey_roi: None,
+            turf_pedigree: Some(ScaledBonus { center: 100.0, divisor: 30.0, min: -0.5, max: 0.8 }),
+            surface_best_speed: None,
+            surface_record: None,
+        }
+    }
+
+    pub fn synthetic() -> Self {
+        Self {
+            pace_heat: PaceHeatRule { min_quirin: 7, min_quirin_ep: Some(6) },
+            epi: EpiBlend { heat_weight: 0.65, early_weight: 0.35, early_offset: 5.0, early_range: 11.0 },
+            shape_cutoffs: ShapeCutoffs { meltdown: 0.85, fast: 0.72, slow: 0.24 },
+            weights: ShapeWeights {
+                slow: ComponentWeights { speed: 0.45, early: 0.30, late: 0.25 },
+                honest: ComponentWeights { speed: 0.42, early: 0.25, late: 0.33 },
+                fast: ComponentWeights { speed: 0.42, early: 0.20, late: 0.38 },
+                meltdown: ComponentWeights { speed: 0.38, early: 0.12, late: 0.50 },
+            },
+            prime_power_divisor: 11.0,
+            layoff_penalty: Some(LayoffPenalty { min_days: 60, days_per_point: 60.0, max_penalty: 1.5 }),
+            trainer_jockey_roi: Some(ScaledBonus { center: 0.0, divisor: 4.0, min: -0.5, max: 0.5 }),
+            turf_pedigree: None,
+            surface_best_speed: Some(ScaledBonus { center: 0.0, divisor: 10.0, min: -0.5, max: 0.5 }),
+            surface_record: Some(SurfaceRecordBonus {
+                min_starts: 2,
+                win_rate: ScaledBonus { center: 0.15, divisor: 0.5, min: -0.3, max: 0.5 },
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WetTrackConfig {
+    pub wet_form: ScaledBonus,
+    pub wet_record: SurfaceRecordBonus,
+    pub mud_pedigree: ScaledBonus,
+}
+
+impl Default for WetTrackConfig {
+    fn default() -> Self {
+        Self {
+            wet_form: ScaledBonus { center: 0.0, divisor: 8.0, min: -0.6, max: 0.6 },
+            wet_record: SurfaceRecordBonus {
+                min_starts: 2,
+                win_rate: ScaledBonus { center: 0.12, divisor: 0.4, min: -0.3, max: 0.6 },
+            },
+            mud_pedigree: ScaledBonus { center: 100.0, divisor: 30.0, min: -0.4, max: 0.7 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FigureAdjustmentConfig {
+    pub variant_baseline: f64,
+    pub variant_factor: f64,
+    pub wet_track_points: f64,
+    pub sealed_track_points: f64,
+    pub off_surface_points: f64,
+    pub track_class: BTreeMap<String, f64>,
+}
+
+impl Default for FigureAdjustmentConfig {
+    fn default() -> Self {
+        Self {
+            variant_baseline: 18.0,
+            variant_factor: 0.1,
+            wet_track_points: 1.0,
+            sealed_track_points: -1.0,
+            off_surface_points: -2.0,
+            track_class: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomFigureConfig {
+    pub par_figure: f64,
+    pub seconds_per_length: f64,
+    pub points_per_length: f64,
+    pub reference_distance: i32,
+    pub min_par_races: u32,
+    pub variant_factor: f64,
+}
+
+impl Default for CustomFigureConfig {
+    fn default() -> Self {
+        Self {
+            par_figure: 80.0,
+            seconds_per_length: 0.2,
+            points_per_length: 2.0,
+            reference_distance: 1320,
+            min_par_races: 3,
+            variant_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrainerAngleConfig {
+    pub min_starts: u32,
+    pub layoff_days: u32,
+    pub route_yards: i32,
+    pub win_pct: ScaledBonus,
+    pub roi: ScaledBonus,
+}
+
+impl Default for TrainerAngleConfig {
+    fn default() -> Self {
+        Self {
+            min_starts: 10,
+            layoff_days: DEFAULT_LAYOFF_DAYS,
+            route_yards: DEFAULT_ROUTE_YARDS,
+            win_pct: ScaledBonus { center: 12.0, divisor: 20.0, min: -0.3, max: 0.5 },
+            roi: ScaledBonus { center: 0.0, divisor: 4.0, min: -0.2, max: 0.3 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EquipmentConfig {
+    pub blinkers_on: f64,
+    pub first_time_blinkers: f64,
+    pub blinkers_off: f64,
+    pub first_time_lasix: f64,
+    pub lasix_off: f64,
+    pub nasal_strip_on: f64,
+    pub nasal_strip_off: f64,
+}
+
+impl Default for EquipmentConfig {
+    fn default() -> Self {
+        Self {
+            blinkers_on: 0.1,
+            first_time_blinkers: 0.2,
+            blinkers_off: 0.0,
+            first_time_lasix: 0.3,
+            lasix_off: -0.3,
+            nasal_strip_on: 0.05,
+            nasal_strip_off: 0.0,
+        }
+    }
+}
+
+impl EquipmentConfig {
+    pub fn points(&self, event: EquipmentEvent) -> f64 {
+        match event {
+            EquipmentEvent::BlinkersOn => self.blinkers_on,
+            EquipmentEvent::FirstTimeBlinkers => self.first_time_blinkers,
+            EquipmentEvent::BlinkersOff => self.blinkers_off,
+            EquipmentEvent::FirstTimeLasix => self.first_time_lasix,
+            EquipmentEvent::LasixOff => self.lasix_off,
+            EquipmentEvent::NasalStripOn => self.nasal_strip_on,
+            EquipmentEvent::NasalStripOff => self.nasal_strip_off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormCycleAdjustments {
+    pub fresh_return: f64,
+    pub workout_backed_return: f64,
+    pub second_off_layoff: f64,
+    pub third_off_layoff: f64,
+    pub bounce: f64,
+    pub improving: f64,
+    pub declining: f64,
+}
+
+impl Default for FormCycleAdjustments {
+    fn default() -> Self {
+        Self {
+            fresh_return: -0.3,
+            workout_backed_return: 0.2,
+            second_off_layoff: 0.25,
+            third_off_layoff: 0.15,
+            bounce: -0.4,
+            improving: 0.3,
+            declining: -0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormCycleConfig {
+    pub layoff_days: u32,
+    pub lookback: usize,
+    pub bounce_margin: f64,
+    pub min_works: u32,
+    pub min_top_works: u32,
+    pub adjustments: FormCycleAdjustments,
+}
+
+impl Default for FormCycleConfig {
+    fn default() -> Self {
+        Self {
+            layoff_days: DEFAULT_LAYOFF_DAYS,
+            lookback: 10,
+            bounce_margin: 5.0,
+            min_works: 3,
+            min_top_works: 1,
+            adjustments: FormCycleAdjustments::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PedigreeSwitchConfig {
+    pub route_yards: i32,
+    pub pedigree: ScaledBonus,
+    pub min_sire_starts: u32,
+    pub sire_win_pct: ScaledBonus,
+    pub log_stud_fee: ScaledBonus,
+    pub dam_sire_weight: f64,
+    pub record_win_pct: ScaledBonus,
+}
+
+impl Default for PedigreeSwitchConfig {
+    fn default() -> Self {
+        Self {
+            route_yards: DEFAULT_ROUTE_YARDS,
+            pedigree: ScaledBonus { center: 100.0, divisor: 25.0, min: -0.5, max: 0.6 },
+            min_sire_starts: 20,
+            sire_win_pct: ScaledBonus { center: 0.10, divisor: 0.2, min: -0.3, max: 0.4 },
+            log_stud_fee: ScaledBonus { center: 8.5, divisor: 5.0, min: -0.2, max: 0.3 },
+            dam_sire_weight: 0.5,
+            record_win_pct: ScaledBonus { center: 0.12, divisor: 0.5, min: -0.2, max: 0.3 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionsConfig {
+    pub min_starts: u32,
+    pub jockey_change_threshold: f64,
+    pub jockey_win_pct_delta: ScaledBonus,
+    pub jockey_roi: ScaledBonus,
+    pub trainer_win_pct_delta: ScaledBonus,
+    pub first_after_claim: f64,
+}
+
+impl Default for ConnectionsConfig {
+    fn default() -> Self {
+        Self {
+            min_starts: 20,
+            jockey_change_threshold: 3.0,
+            jockey_win_pct_delta: ScaledBonus { center: 0.0, divisor: 20.0, min: -0.3, max: 0.3 },
+            jockey_roi: ScaledBonus { center: 0.0, divisor: 10.0, min: -0.1, max: 0.1 },
+            trainer_win_pct_delta: ScaledBonus { center: 0.0, divisor: 20.0, min: -0.3, max: 0.4 },
+            first_after_claim: 0.15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebutModelConfig {
+    pub max_starts: usize,
+    pub base: f64,
+    pub partial_figure_weight: f64,
+    pub workout: ScaledBonus,
+    pub log_stud_fee: ScaledBonus,
+    pub pedigree: ScaledBonus,
+    pub log_auction_price: ScaledBonus,
+    pub first_time_starter_win_pct: ScaledBonus,
+    pub first_time_starter_min_starts: u32,
+    pub morning_line_edge: ScaledBonus,
+}
+
+impl Default for DebutModelConfig {
+    fn default() -> Self {
+        Self {
+            max_starts: 2,
+            base: -1.5,
+            partial_figure_weight: 0.5,
+            workout: ScaledBonus { center: 0.5, divisor: 1.0, min: -0.5, max: 1.0 },
+            log_stud_fee: ScaledBonus { center: 8.5, divisor: 2.5, min: -0.5, max: 1.0 },
+            pedigree: ScaledBonus { center: 100.0, divisor: 25.0, min: -0.5, max: 0.8 },
+            log_auction_price: ScaledBonus { center: 10.3, divisor: 3.0, min: -0.4, max: 0.6 },
+            first_time_starter_win_pct: ScaledBonus { center: 12.0, divisor: 10.0, min: -0.5, max: 0.8 },
+            first_time_starter_min_starts: 10,
+            morning_line_edge: ScaledBonus { center: 0.0, divisor: 0.15, min: -0.8, max: 1.5 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStyleOffsets {
+    pub e: f64,
+    pub ep: f64,
+    pub p: f64,
+    pub s: f64,
+}
+
+impl Default for RunStyleOffsets {
+    fn default() -> Self {
+        Self { e: 3.0, ep: 1.5, p: -1.0, s: -3.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaceProjectionConfig {
+    pub first_call_lengths_per_point: f64,
+    pub second_call_lengths_per_point: f64,
+    pub stretch_lengths_per_point: f64,
+    pub run_style_offsets: RunStyleOffsets,
+    pub quirin_center: f64,
+    pub quirin_early_points: f64,
+    pub quirin_bonus: f64,
+    pub missing_early_penalty: f64,
+    pub sprint_max_furlongs: f64,
+    pub sprint_post_penalty: f64,
+    pub route_post_penalty: f64,
+    pub duel_lengths: f64,
+    pub two_horse_duel_penalty: f64,
+    pub multi_horse_duel_penalty: f64,
+    pub second_call_early_weight: f64,
+    pub second_call_speed_weight: f64,
+    pub second_call_pressure_weight: f64,
+    pub stretch_early_weight: f64,
+    pub stretch_finish_weight: f64,
+    pub stretch_pressure_weight: f64,
+    pub front_lengths: f64,
+    pub stalking_lengths: f64,
+    pub midpack_lengths: f64,
+}
+
+impl Default for PaceProjectionConfig {
+    fn default() -> Self {
+        Self {
+            first_call_lengths_per_point: 0.5,
+            second_call_lengths_per_point: 0.6,
+            stretch_lengths_per_point: 0.4,
+            run_style_offsets: RunStyleOffsets::default(),
+            quirin_center: 4.0,
+            quirin_early_points: 1.5,
+            quirin_bonus: 0.5,
+            missing_early_penalty: 2.0,
+            sprint_max_furlongs: 7.0,
+            sprint_post_penalty: 0.15,
+            route_post_penalty: 0.25,
+            duel_lengths: 1.0,
+            two_horse_duel_penalty: 1.0,
+            multi_horse_duel_penalty: 2.0,
+            second_call_early_weight: 0.8,
+            second_call_speed_weight: 0.2,
+            second_call_pressure_weight: 0.5,
+            stretch_early_weight: 0.3,
+            stretch_finish_weight: 0.7,
+            stretch_pressure_weight: 1.0,
+            front_lengths: 1.0,
+            stalking_lengths: 4.0,
+            midpack_lengths: 8.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceThresholds {
+    pub strong_single_gap: f64,
+    pub strong_single_spread: f64,
+    pub playable_gap: f64,
+    pub competitive_gap: f64,
+    pub competitive_spread: f64,
+}
+
+impl Default for ConfidenceThresholds {
+    fn default() -> Self {
+        Self {
+            strong_single_gap: 2.0,
+            strong_single_spread: 3.0,
+            playable_gap: 1.5,
+            competitive_gap: 0.5,
+            competitive_spread: 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelConfig {
+    pub recency_weights: Vec<f64>,
+    pub min_same_surface_pps: usize,
+    #[serde(deserialize_with = "optional_section")]
+    pub figure_adjustments: Option<FigureAdjustmentConfig>,
+    #[serde(deserialize_with = "section")]
+    pub custom_figures: CustomFigureConfig,
+    #[serde(deserialize_with = "class_change_bonus")]
+    pub class_change: Option<ScaledBonus>,
+    #[serde(deserialize_with = "optional_section")]
+    pub trainer_angles: Option<TrainerAngleConfig>,
+    #[serde(deserialize_with = "optional_section")]
+    pub equipment: Option<EquipmentConfig>,
+    #[serde(deserialize_with = "optional_section")]
+    pub form_cycle: Option<FormCycleConfig>,
+    #[serde(deserialize_with = "optional_section")]
+    pub pedigree_switch: Option<PedigreeSwitchConfig>,
+    #[serde(deserialize_with = "optional_section")]
+    pub connections: Option<ConnectionsConfig>,
+    pub workout_days_window: i64,
+    pub win_probability_temperature: f64,
+    #[serde(deserialize_with = "dirt_surface")]
+    pub dirt: SurfaceModelConfig,
+    #[serde(deserialize_with = "turf_surface")]
+    pub turf: SurfaceModelConfig,
+    #[serde(deserialize_with = "synthetic_surface")]
+    pub synthetic: SurfaceModelConfig,
+    #[serde(deserialize_with = "section")]
+    pub wet_track: WetTrackConfig,
+    #[serde(deserialize_with = "section")]
+    pub pace_projection: PaceProjectionConfig,
+    #[serde(deserialize_with = "optional_section")]
+    pub debut: Option<DebutModelConfig>,
+    #[serde(deserialize_with = "section")]
+    pub confidence: ConfidenceThresholds,
+    #[serde(deserialize_with = "section")]
+    pub win_bet: WinBetOptions,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            recency_weights: vec![0.55, 0.30, 0.15, 0.08, 0.05],
+            min_same_surface_pps: 2,
+            figure_adjustments: None,
+            custom_figures: CustomFigureConfig::default(),
+            class_change: None,
+            trainer_angles: None,
+            equipment: None,
+            form_cycle: None,
+            pedigree_switch: None,
+            connections: None,
+            workout_days_window: 21,
+            win_probability_temperature: 1.0,
+            dirt: SurfaceModelConfig::dirt(),
+            turf: SurfaceModelConfig::turf(),
+            synthetic: SurfaceModelConfig::synthetic(),
+            wet_track: WetTrackConfig::default(),
+            pace_projection: PaceProjectionConfig::default(),
+            debut: None,
+            confidence: ConfidenceThresholds::default(),
+            win_bet: WinBetOptions::default(),
+        }
+    }
+}
+
+fn merge_json(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+// A partial section only overrides the fields it names, down to single fields of a nested
+// bonus; everything else keeps that section's own defaults.
+fn with_defaults<'de, D: Deserializer<'de>, T: Serialize + DeserializeOwned>(
+    defaults: T,
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let patch = Value::deserialize(deserializer)?;
+    let mut merged = serde_json::to_value(defaults).map_err(D::Error::custom)?;
+    merge_json(&mut merged, patch);
+    serde_json::from_value(merged).map_err(D::Error::custom)
+}
+
+// Optional factors are off when null, and merge over their defaults when switched on.
+fn optional_with_defaults<'de, D: Deserializer<'de>, T: Serialize + DeserializeOwned>(
+    defaults: T,
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    match Option::<Value>::deserialize(deserializer)? {
+        Some(patch) => with_defaults(defaults, patch).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn section<'de, D: Deserializer<'de>, T: Default + Serialize + DeserializeOwned>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    with_defaults(T::default(), deserializer)
+}
+
+fn optional_section<'de, D: Deserializer<'de>, T: Default + Serialize + DeserializeOwned>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    optional_with_defaults(T::default(), deserializer)
+}
+
+fn class_change_bonus<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ScaledBonus>, D::Error> {
+    optional_with_defaults(DEFAULT_CLASS_CHANGE, deserializer)
+}
+
+fn dirt_surface<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SurfaceModelConfig, D::Error> {
+    with_defaults(SurfaceModelConfig::dirt(), deserializer)
+}
+
+fn turf_surface<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SurfaceModelConfig, D::Error> {
+    with_defaults(SurfaceModelConfig::turf(), deserializer)
+}
+
+fn synthetic_surface<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SurfaceModelConfig, D::Error> {
+    with_defaults(SurfaceModelConfig::synthetic(), deserializer)
+}
+
+impl ModelConfig {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn surface(&self, mode: SurfaceMode) -> &SurfaceModelConfig {
+        match mode {
+            SurfaceMode::Dirt => &self.dirt,
+            SurfaceMode::Turf => &self.turf,
+            SurfaceMode::Synthetic => &self.synthetic,
+        }
+    }
+
+    pub async fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read model config file: {}", e))?;
+
+        Self::from_json(&contents).map_err(|e| format!("Failed to parse model config: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextual_speed_and_pace_model::params_hash;
+
+    #[test]
+    fn partial_sections_merge_with_their_defaults() {
+        let json = r#"{"confidence":{"playable_gap":1.0},"wet_track":{"wet_form":{"max":0.9}}}"#;
+        let config = ModelConfig::from_json(json).unwrap();
+        let defaults = ModelConfig::default();
+
+        assert_eq!(config.confidence.playable_gap, 1.0);
+        assert_eq!(config.confidence.strong_single_gap, defaults.confidence.strong_single_gap);
+        assert_eq!(config.wet_track.wet_form.max, 0.9);
+        assert_eq!(config.wet_track.wet_form.divisor, defaults.wet_track.wet_form.divisor);
+        assert_eq!(config.wet_track.mud_pedigree, defaults.wet_track.mud_pedigree);
+    }
+
+    #[test]
+    fn optional_factors_merge_when_switched_on() {
+        let json = r#"{"trainer_angles":{"roi":{"max":1.0}},"class_change":{"divisor":10.0}}"#;
+        let config = ModelConfig::from_json(json).unwrap();
+
+        let angles = config.trainer_angles.unwrap();
+        assert_eq!(angles.roi.max, 1.0);
+        assert_eq!(angles.roi.divisor, TrainerAngleConfig::default().roi.divisor);
+        assert_eq!(angles.min_starts, TrainerAngleConfig::default().min_starts);
+        assert_eq!(config.class_change, Some(ScaledBonus { divisor: 10.0, ..DEFAULT_CLASS_CHANGE }));
+
+        let off = ModelConfig::from_json(r#"{"trainer_angles":null}"#).unwrap();
+        assert_eq!(off, ModelConfig::default());
+    }
+
+    #[test]
+    fn partial_surfaces_keep_their_own_defaults() {
+        let config = ModelConfig::from_json(r#"{"turf":{"prime_power_divisor":9.0}}"#).unwrap();
+        assert_eq!(config.turf.prime_power_divisor, 9.0);
+        assert_eq!(config.turf.weights, SurfaceModelConfig::turf().weights);
+        assert_eq!(config.dirt, SurfaceModelConfig::dirt());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let config = ModelConfig { form_cycle: Some(FormCycleConfig::default()), ..ModelConfig::default() };
+        assert_eq!(ModelConfig::from_json(&config.to_json().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn custom_figure_settings_do_not_change_the_params_hash() {
+        let base = ModelConfig::default();
+        let custom = ModelConfig {
+            custom_figures: CustomFigureConfig { par_figure: 90.0, ..CustomFigureConfig::default() },
+            ..base.clone()
+        };
+        let weighted = ModelConfig { win_probability_temperature: 2.0, ..base.clone() };
+
+        assert_eq!(params_hash(&custom), params_hash(&base));
+        assert_ne!(params_hash(&weighted), params_hash(&base));
+    }
+}
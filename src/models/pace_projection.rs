@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use crate::contextual_speed_and_pace_model::{RunStyle, Shape, SurfaceMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedMapPosition {
+    Front,
+    Stalking,
+    Midpack,
+    Rear,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallPosition {
+    pub position: u32,
+    pub beaten_lengths: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaceProjectionHorse {
+    pub program_number: String,
+    pub horse_name: String,
+    pub post_position: Option<u32>,
+    pub run_style: RunStyle,
+    pub quirin: Option<u32>,
+    pub speed_map_position: SpeedMapPosition,
+    pub first_call: CallPosition,
+    pub second_call: CallPosition,
+    pub stretch: CallPosition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedMapGroup {
+    pub position: SpeedMapPosition,
+    pub program_numbers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaceProjection {
+    pub race_number: Option<u32>,
+    pub surface_mode: SurfaceMode,
+    pub distance_f: f64,
+    pub shape: Shape,
+    pub horses: Vec<PaceProjectionHorse>,
+    pub early_speed_map: Vec<SpeedMapGroup>,
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use crate::models::backtest::BacktestStats;
+use crate::models::model_config::ModelConfig;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum SearchStrategy {
+    Grid,
+    Random { iterations: u32, seed: u64 },
+    CoordinateDescent { max_rounds: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum SearchObjective {
+    TopPickWinPct,
+    TopPickRoi,
+    WinBetRoi,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub path: String,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterValue {
+    pub path: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSearchOptions {
+    pub strategy: SearchStrategy,
+    pub objective: SearchObjective,
+    pub parameters: Vec<ParameterRange>,
+    pub validation_from_date: String,
+    pub min_bets: u32,
+    pub max_evaluations: Option<u32>,
+    pub stake: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchTrial {
+    pub params: Vec<ParameterValue>,
+    pub train_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSearchReport {
+    pub strategy: SearchStrategy,
+    pub objective: SearchObjective,
+    pub train_races: u32,
+    pub validation_races: u32,
+    pub evaluations: u32,
+    pub baseline_train_score: Option<f64>,
+    pub baseline_validation_score: Option<f64>,
+    pub best_params: Vec<ParameterValue>,
+    pub best_train_score: Option<f64>,
+    pub best_validation_score: Option<f64>,
+    pub best_train: BacktestStats,
+    pub best_validation: BacktestStats,
+    pub best_config: ModelConfig,
+    pub trials: Vec<SearchTrial>,
+}
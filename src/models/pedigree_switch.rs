@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwitchKind {
+    FirstTurf,
+    FirstRoute,
+    FirstSynthetic,
+}
+
+impl SwitchKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SwitchKind::FirstTurf => "first_turf",
+            SwitchKind::FirstRoute => "first_route",
+            SwitchKind::FirstSynthetic => "first_synthetic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SireStats {
+    pub sire: String,
+    pub offspring: u32,
+    pub turf_starts: u32,
+    pub turf_wins: u32,
+    pub synthetic_starts: u32,
+    pub synthetic_wins: u32,
+    pub route_starts: u32,
+    pub route_wins: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchSuitability {
+    pub kind: SwitchKind,
+    pub pedigree_rating: Option<f64>,
+    pub sire_win_pct: Option<f64>,
+    pub dam_sire_win_pct: Option<f64>,
+    #[serde(default)]
+    pub sire_stud_fee: Option<u32>,
+    pub record_starts: Option<u32>,
+    pub record_win_pct: Option<f64>,
+    pub score: f64,
+}
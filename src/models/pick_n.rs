@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use crate::contextual_speed_and_pace_model::Confidence;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickNOptions {
+    pub legs: u32,
+    pub base_amount: f64,
+    pub budget: f64,
+}
+
+impl Default for PickNOptions {
+    fn default() -> Self {
+        Self {
+            legs: 4,
+            base_amount: 0.5,
+            budget: 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickNLeg {
+    pub race_number: Option<u32>,
+    pub confidence: Confidence,
+    pub field_size: u32,
+    pub selections: Vec<String>,
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickNTicket {
+    pub pool: String,
+    pub start_race: u32,
+    pub legs: Vec<PickNLeg>,
+    pub combinations: u32,
+    pub base_amount: f64,
+    pub cost: f64,
+    pub coverage_probability: f64,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use crate::contextual_speed_and_pace_model::RaceMeta;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceMetaSnapshot {
+    pub id: i64,
+    pub race_id: i64,
+    pub model_version: String,
+    pub params_hash: String,
+    pub created_at: String,
+    pub race_meta: RaceMeta,
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RaceResult {
+    pub id: i64,
+    pub race_id: Option<i64>,
+    pub track_code: String,
+    pub race_date: String,
+    pub race_number: Option<u32>,
+    pub distance: Option<i32>,
+    pub surface: String,
+    pub track_condition: String,
+    pub final_time: Option<f64>,
+    pub source_file: String,
+    pub starters: Vec<ResultStarter>,
+    pub payouts: Vec<ResultPayout>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultStarter {
+    pub id: i64,
+    pub race_result_id: i64,
+    pub horse_id: Option<i64>,
+    pub program_number: String,
+    pub horse_name: String,
+    pub post_position: Option<u32>,
+    pub finish_position: Option<u32>,
+    pub margin: Option<f64>,
+    pub beaten_lengths: Option<f64>,
+    pub final_odds: Option<f64>,
+    pub win_payoff: Option<f64>,
+    pub place_payoff: Option<f64>,
+    pub show_payoff: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultPayout {
+    pub id: i64,
+    pub race_result_id: i64,
+    pub pool: String,
+    pub base_amount: Option<f64>,
+    pub winning_numbers: String,
+    pub payoff: Option<f64>,
+    pub pool_total: Option<f64>,
+}
+
+impl RaceResult {
+    // Dead heats list more than one starter in first.
+    pub fn winners(&self) -> Vec<&ResultStarter> {
+        self.starters.iter().filter(|s| s.finish_position == Some(1)).collect()
+    }
+
+    pub fn finish_order(&self) -> Vec<&ResultStarter> {
+        let mut order: Vec<&ResultStarter> = self
+            .starters
+            .iter()
+            .filter(|s| s.finish_position.is_some())
+            .collect();
+        order.sort_by_key(|s| s.finish_position);
+        order
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Racecard {
     pub id: i64,
     pub zip_file_name: String,
@@ -12,6 +12,17 @@ pub struct Racecard {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RacecardSummary {
+    pub id: i64,
+    pub zip_file_name: String,
+    pub track_code: String,
+    pub track: String,
+    pub date: String,
+    pub long_date: String,
+    pub race_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Race {
     pub id: i64,
     pub racecard_id: i64,
@@ -60,10 +71,11 @@ pub struct Race {
     pub horses: Vec<Horse>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Horse {
     pub id: i64,
     pub race_id: i64,
+    pub horse_identity_id: Option<i64>,
     pub scratched: bool,
     pub trip_handicapping_info: String,
     pub post_position: Option<u32>,
@@ -213,7 +225,7 @@ pub struct Horse {
     pub key_trainer_stats: Vec<KeyTrainerStat>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Workout {
     pub id: i64,
     pub horse_id: i64,
@@ -228,7 +240,7 @@ pub struct Workout {
     pub rank: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PastPerformance {
     pub id: i64,
     pub horse_id: i64,
@@ -334,7 +346,7 @@ pub struct PastPerformance {
     pub equibase_abbreviated_race_condition: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct KeyTrainerStat {
     pub id: i64,
     pub horse_id: i64,
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum StakingStrategy {
+    Flat { amount: f64 },
+    PercentOfBankroll { fraction: f64 },
+    Kelly { multiplier: f64, max_fraction: Option<f64> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakedBet {
+    pub probability: f64,
+    pub odds: f64,
+    pub won: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankrollStep {
+    pub stake: f64,
+    pub won: bool,
+    pub bankroll: f64,
+    pub drawdown: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankrollSimulation {
+    pub strategy: StakingStrategy,
+    pub starting_bankroll: f64,
+    pub ending_bankroll: f64,
+    pub peak_bankroll: f64,
+    pub growth: f64,
+    pub max_drawdown: f64,
+    pub max_drawdown_amount: f64,
+    pub bets_placed: u32,
+    pub wins: u32,
+    pub total_staked: f64,
+    pub busted: bool,
+    pub steps: Vec<BankrollStep>,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrainerAngleKind {
+    FirstTimeStarter,
+    SecondCareerStart,
+    FirstTimeLasix,
+    BlinkersOn,
+    BlinkersOff,
+    FirstAfterClaim,
+    FirstWithTrainer,
+    FirstOffLayoff,
+    SecondOffLayoff,
+    Days31To60,
+    Days61To180,
+    Days180Plus,
+    Shipper,
+    DirtToTurf,
+    TurfToDirt,
+    SprintToRoute,
+    RouteToSprint,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainerAngle {
+    pub kind: TrainerAngleKind,
+    pub category: String,
+    pub starts: Option<u32>,
+    pub win_pct: Option<f64>,
+    pub roi: Option<f64>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrainerAngleSummary {
+    pub angles: Vec<TrainerAngle>,
+    pub score: Option<f64>,
+}
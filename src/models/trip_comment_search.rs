@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TripCommentSearchFilters {
+    pub track_code: Option<String>,
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    pub surface: Option<String>,
+    pub horse_identity_id: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripCommentHit {
+    pub racecard_id: i64,
+    pub race_id: i64,
+    pub race_number: Option<u32>,
+    pub horse_id: Option<i64>,
+    pub horse_name: String,
+    pub program_number: String,
+    pub past_performance_id: Option<i64>,
+    pub track_code: String,
+    pub race_date: String,
+    pub surface: String,
+    pub trip_comment: String,
+    pub extended_start_comment: String,
+    pub race_conditions: String,
+    pub rank: f64,
+}
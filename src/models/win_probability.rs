@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureFit {
+    pub temperature: f64,
+    pub races: u32,
+    pub log_loss: f64,
+    pub uniform_log_loss: f64,
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use chrono::NaiveDate;
+use sqlx::{Row, SqlitePool};
+use crate::models::backtest::{BacktestFilters, BacktestRace};
+use crate::models::model_config::ModelConfig;
+use crate::sqlite::race_results::read_race_results_for_racecard;
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::sqlite::sire_stats::read_sire_stats;
+
+// Card dates are stored as yyyymmdd and compared as text, so any other format would silently match nothing.
+fn filter_date(value: &Option<String>) -> Result<Option<String>, sqlx::Error> {
+    value
+        .as_deref()
+        .map(|v| {
+            let v = v.trim();
+            NaiveDate::parse_from_str(v, "%Y%m%d")
+                .map(|_| v.to_string())
+                .map_err(|_| sqlx::Error::Encode(format!("invalid date `{v}`, expected yyyymmdd").into()))
+        })
+        .transpose()
+}
+
+pub async fn load_backtest_races(
+    pool: &SqlitePool,
+    filters: &BacktestFilters,
+    config: &ModelConfig,
+) -> Result<Vec<BacktestRace>, sqlx::Error> {
+    let track_code = filters.track_code.as_ref().map(|t| t.trim().to_lowercase());
+    let from_date = filter_date(&filters.from_date)?;
+    let to_date = filter_date(&filters.to_date)?;
+    let sire_stats = match &config.pedigree_switch {
+        Some(pedigree_switch) => read_sire_stats(pool, pedigree_switch.route_yards).await?,
+        None => Vec::new(),
+    };
+    let sire_stats = Arc::new(sire_stats);
+    let racecard_ids: Vec<i64> = sqlx::query(
+        r#"
+        SELECT DISTINCT rc.id, rc.date
+        FROM racecards rc
+        JOIN races r ON r.racecard_id = rc.id
+        JOIN race_results rr ON rr.race_id = r.id
+        WHERE (? IS NULL OR lower(rc.track_code) = ?)
+            AND (? IS NULL OR rc.date >= ?)
+            AND (? IS NULL OR rc.date <= ?)
+        ORDER BY rc.date, rc.id;
+        "#,
+    )
+    .bind(&track_code)
+    .bind(&track_code)
+    .bind(&from_date)
+    .bind(&from_date)
+    .bind(&to_date)
+    .bind(&to_date)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| row.get("id"))
+    .collect();
+
+    let mut backtest_races = Vec::new();
+
+    for racecard_id in racecard_ids {
+        let racecard = match read_racecard_by_id(pool, racecard_id).await? {
+            Some(rc) => Arc::new(rc),
+            None => continue,
+        };
+        let mut results = read_race_results_for_racecard(pool, racecard_id).await?;
+
+        for race in &racecard.races {
+            let result = match results.iter().position(|r| r.race_id == Some(race.id)) {
+                Some(i) => results.swap_remove(i),
+                None => continue,
+            };
+
+            backtest_races.push(BacktestRace {
+                racecard_id,
+                track_code: racecard.track_code.clone(),
+                date: racecard.date.clone(),
+                race: race.clone(),
+                result,
+                racecard: Some(Arc::clone(&racecard)),
+                sire_stats: Arc::clone(&sire_stats),
+            });
+        }
+    }
+
+    Ok(backtest_races)
+}
@@ -0,0 +1,57 @@
+use sqlx::{Row, SqlitePool};
+use crate::models::custom_figures::FigurePar;
+use crate::sqlite::racecards::opt_f64;
+
+pub async fn read_figure_pars(pool: &SqlitePool, min_races: u32) -> Result<Vec<FigurePar>, sqlx::Error> {
+    // The same race shows up once per horse that ran in it, so collapse to one row per race first.
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            track_code,
+            surface,
+            distance,
+            COUNT(*) AS races,
+            AVG(fraction_1) AS fraction_1,
+            AVG(fraction_2) AS fraction_2,
+            AVG(fraction_3) AS fraction_3,
+            AVG(final_time) AS final_time,
+            AVG(track_variant) AS track_variant
+        FROM (
+            SELECT DISTINCT
+                upper(trim(track_code)) AS track_code,
+                trim(surface) AS surface,
+                distance,
+                race_date,
+                race_number,
+                fraction_1,
+                fraction_2,
+                fraction_3,
+                final_time,
+                track_variant
+            FROM past_performances
+            WHERE distance IS NOT NULL AND final_time IS NOT NULL AND final_time > 0
+        )
+        GROUP BY track_code, surface, distance
+        HAVING COUNT(*) >= ?
+        ORDER BY track_code, surface, distance;
+        "#,
+    )
+    .bind(min_races as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| FigurePar {
+            track_code: row.get("track_code"),
+            surface: row.get("surface"),
+            distance: row.get::<i64, _>("distance") as i32,
+            races: row.get::<i64, _>("races") as u32,
+            fraction_1: opt_f64(row, "fraction_1"),
+            fraction_2: opt_f64(row, "fraction_2"),
+            fraction_3: opt_f64(row, "fraction_3"),
+            final_time: opt_f64(row, "final_time"),
+            track_variant: opt_f64(row, "track_variant"),
+        })
+        .collect())
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use chrono::NaiveDate;
+use sqlx::{Row, SqliteConnection, SqlitePool, sqlite::SqliteRow};
+use crate::models::horse_identity::{HorseEntry, HorseIdentity};
+use crate::models::racecard::{Horse, PastPerformance};
+use crate::sqlite::racecards::{opt_u32, past_performance_from_row};
+
+fn horse_identity_from_row(row: &SqliteRow) -> HorseIdentity {
+    HorseIdentity {
+        id: row.get("id"),
+        horse_name: row.get("horse_name"),
+        year_of_birth: opt_u32(row, "year_of_birth"),
+        dam: row.get("dam"),
+        sire: row.get("sire"),
+    }
+}
+
+fn horse_entry_from_row(row: &SqliteRow) -> HorseEntry {
+    HorseEntry {
+        horse_id: row.get("horse_id"),
+        horse_identity_id: row.get("horse_identity_id"),
+        race_id: row.get("race_id"),
+        racecard_id: row.get("racecard_id"),
+        track_code: row.get("track_code"),
+        track: row.get("track"),
+        date: row.get("date"),
+        race_number: opt_u32(row, "race_number"),
+        program_number: row.get("program_number"),
+        scratched: row.get::<i64, _>("scratched") != 0,
+    }
+}
+
+// A name and foaling year alone are shared by unrelated horses, so a horse needs a dam or
+// sire on record before it is matched to an identity.
+pub(crate) fn has_pedigree(dam: &str, sire: &str) -> bool {
+    !dam.trim().is_empty() || !sire.trim().is_empty()
+}
+
+pub(crate) async fn resolve_horse_identity(
+    conn: &mut SqliteConnection,
+    horse: &Horse,
+) -> Result<Option<i64>, sqlx::Error> {
+    if !has_pedigree(&horse.dam, &horse.sire) {
+        return Ok(None);
+    }
+    let id = resolve_identity(conn, &horse.horse_name, horse.year_of_birth, &horse.dam, &horse.sire).await?;
+    Ok(Some(id))
+}
+
+async fn resolve_identity(
+    conn: &mut SqliteConnection,
+    horse_name: &str,
+    year_of_birth: Option<u32>,
+    dam: &str,
+    sire: &str,
+) -> Result<i64, sqlx::Error> {
+    let horse_name = horse_name.trim();
+    let dam = dam.trim();
+    let sire = sire.trim();
+
+    let existing = sqlx::query(
+        r#"
+        SELECT id FROM horse_identity
+        WHERE horse_name = ? AND year_of_birth IS ? AND dam = ? AND sire = ?;
+        "#,
+    )
+    .bind(horse_name)
+    .bind(year_of_birth)
+    .bind(dam)
+    .bind(sire)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(row) = existing {
+        return Ok(row.get("id"));
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO horse_identity (
+            horse_name,
+            year_of_birth,
+            dam,
+            sire
+        )
+        VALUES (?, ?, ?, ?);
+        "#,
+    )
+    .bind(horse_name)
+    .bind(year_of_birth)
+    .bind(dam)
+    .bind(sire)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub(crate) async fn migrate_horse_identities(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns = sqlx::query("PRAGMA table_info(horses);")
+        .fetch_all(pool)
+        .await?;
+    let has_identity_column = columns
+        .iter()
+        .any(|row| row.get::<String, _>("name") == "horse_identity_id");
+
+    if !has_identity_column {
+        sqlx::query("ALTER TABLE horses ADD COLUMN horse_identity_id INTEGER REFERENCES horse_identity(id);")
+            .execute(pool)
+            .await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_horses_horse_identity_id ON horses(horse_identity_id);")
+        .execute(pool)
+        .await?;
+
+    let unlinked = sqlx::query(
+        r#"
+        SELECT id, horse_name, year_of_birth, dam, sire FROM horses
+        WHERE horse_identity_id IS NULL AND (TRIM(dam) <> '' OR TRIM(sire) <> '')
+        ORDER BY id;
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if unlinked.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    for row in unlinked {
+        let horse_id: i64 = row.get("id");
+        let identity_id = resolve_identity(
+            &mut tx,
+            row.get("horse_name"),
+            opt_u32(&row, "year_of_birth"),
+            row.get("dam"),
+            row.get("sire"),
+        )
+        .await?;
+
+        sqlx::query("UPDATE horses SET horse_identity_id = ? WHERE id = ?;")
+            .bind(identity_id)
+            .bind(horse_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn get_horse_identity(
+    pool: &SqlitePool,
+    horse_identity_id: i64,
+) -> Result<Option<HorseIdentity>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM horse_identity WHERE id = ?;")
+        .bind(horse_identity_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|r| horse_identity_from_row(&r)))
+}
+
+pub async fn find_horse_identities(
+    pool: &SqlitePool,
+    horse_name: &str,
+) -> Result<Vec<HorseIdentity>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM horse_identity WHERE horse_name = ? ORDER BY year_of_birth, id;")
+        .bind(horse_name.trim())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(horse_identity_from_row).collect())
+}
+
+pub async fn horse_entries(
+    pool: &SqlitePool,
+    horse_identity_id: i64,
+) -> Result<Vec<HorseEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            h.id AS horse_id,
+            h.horse_identity_id,
+            h.race_id,
+            r.racecard_id,
+            rc.track_code,
+            rc.track,
+            rc.date,
+            r.race_number,
+            h.program_number,
+            h.scratched
+        FROM horses h
+        JOIN races r ON r.id = h.race_id
+        JOIN racecards rc ON rc.id = r.racecard_id
+        WHERE h.horse_identity_id = ?
+        ORDER BY rc.date, r.race_number, h.id;
+        "#,
+    )
+    .bind(horse_identity_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(horse_entry_from_row).collect())
+}
+
+pub async fn horse_career(
+    pool: &SqlitePool,
+    horse_identity_id: i64,
+) -> Result<Vec<PastPerformance>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT pp.* FROM past_performances pp
+        JOIN horses h ON h.id = pp.horse_id
+        WHERE h.horse_identity_id = ?
+        ORDER BY h.id DESC, pp.id;
+        "#,
+    )
+    .bind(horse_identity_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut seen: HashSet<(String, String, Option<u32>)> = HashSet::new();
+    let mut career: Vec<PastPerformance> = Vec::new();
+
+    for row in rows {
+        let pp = past_performance_from_row(&row);
+        let key = (pp.race_date.clone(), pp.track_code.clone(), pp.race_number);
+        if seen.insert(key) {
+            career.push(pp);
+        }
+    }
+
+    career.sort_by(|a, b| {
+        let da = NaiveDate::parse_from_str(&a.race_date, "%m/%d/%Y").ok();
+        let db = NaiveDate::parse_from_str(&b.race_date, "%m/%d/%Y").ok();
+        db.cmp(&da)
+    });
+
+    Ok(career)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::{Race, Racecard};
+    use crate::sqlite::racecards::add_racecard;
+    use crate::sqlite::test_pool;
+
+    fn horse(name: &str, dam: &str, sire: &str, race_date: &str) -> Horse {
+        Horse {
+            horse_name: name.to_string(),
+            year_of_birth: Some(2022),
+            dam: dam.to_string(),
+            sire: sire.to_string(),
+            program_number: "1".to_string(),
+            past_performances: vec![PastPerformance {
+                race_date: race_date.to_string(),
+                track_code: "SAR".to_string(),
+                race_number: Some(3),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn racecard(date: &str, horses: Vec<Horse>) -> Racecard {
+        Racecard {
+            track_code: "SAR".to_string(),
+            date: date.to_string(),
+            races: vec![Race { race_number: Some(1), horses, ..Default::default() }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn links_the_same_horse_across_cards() {
+        let pool = test_pool().await;
+        let first = add_racecard(&pool, racecard("20260101", vec![horse("Foo", "Bar", "Baz", "12/01/2025")]))
+            .await
+            .unwrap();
+        let second = add_racecard(&pool, racecard("20260201", vec![horse(" FOO ", "bar", "BAZ", "12/01/2025")]))
+            .await
+            .unwrap();
+
+        let id = first.races[0].horses[0].horse_identity_id.unwrap();
+        assert_eq!(second.races[0].horses[0].horse_identity_id, Some(id));
+
+        let identity = get_horse_identity(&pool, id).await.unwrap().unwrap();
+        assert_eq!((identity.horse_name.as_str(), identity.year_of_birth), ("Foo", Some(2022)));
+        assert_eq!(find_horse_identities(&pool, "foo").await.unwrap().len(), 1);
+        assert_eq!(horse_entries(&pool, id).await.unwrap().len(), 2);
+        assert_eq!(horse_career(&pool, id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_different_pedigrees_apart() {
+        let pool = test_pool().await;
+        let horses = vec![horse("Foo", "Bar", "Baz", "12/01/2025"), horse("Foo", "Qux", "Baz", "12/01/2025")];
+        let card = add_racecard(&pool, racecard("20260101", horses)).await.unwrap();
+
+        let horses = &card.races[0].horses;
+        assert_ne!(horses[0].horse_identity_id, horses[1].horse_identity_id);
+    }
+
+    #[tokio::test]
+    async fn leaves_horses_without_pedigree_unlinked() {
+        let pool = test_pool().await;
+        let card = add_racecard(&pool, racecard("20260101", vec![horse("Foo", "", " ", "12/01/2025")]))
+            .await
+            .unwrap();
+
+        assert_eq!(card.races[0].horses[0].horse_identity_id, None);
+        assert!(find_horse_identities(&pool, "Foo").await.unwrap().is_empty());
+    }
+}
@@ -1 +1,20 @@
-pub mod racecards;
\ No newline at end of file
+pub mod racecards;
+pub mod horse_identities;
+pub mod race_results;
+pub mod race_meta_snapshots;
+pub mod trip_comment_search;
+pub mod backtest;
+pub mod figure_pars;
+pub mod sire_stats;
+
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::SqlitePool {
+    // One connection, since every connection to `sqlite::memory:` opens its own database.
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    racecards::create_tables(&pool).await.unwrap();
+    pool
+}
@@ -0,0 +1,151 @@
+use chrono::Utc;
+use sqlx::{Row, SqlitePool, sqlite::SqliteRow};
+use crate::contextual_speed_and_pace_model::{
+    MODEL_VERSION, RaceMeta, RankContext, derive_race_meta_with_context, params_hash,
+};
+use crate::models::model_config::ModelConfig;
+use crate::models::race_meta_snapshot::RaceMetaSnapshot;
+use crate::models::racecard::Racecard;
+use crate::sqlite::sire_stats::read_sire_stats;
+use crate::utils::transformers::Transformers;
+
+fn race_meta_snapshot_from_row(row: &SqliteRow) -> Result<RaceMetaSnapshot, sqlx::Error> {
+    let race_meta_json: String = row.get("race_meta");
+    let race_meta: RaceMeta = serde_json::from_str(&race_meta_json)
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    Ok(RaceMetaSnapshot {
+        id: row.get("id"),
+        race_id: row.get("race_id"),
+        model_version: row.get("model_version"),
+        params_hash: row.get("params_hash"),
+        created_at: row.get("created_at"),
+        race_meta,
+    })
+}
+
+pub async fn add_race_meta_snapshot(
+    pool: &SqlitePool,
+    race_id: i64,
+    race_meta: &RaceMeta,
+    model_version: &str,
+    params_hash: &str,
+) -> Result<RaceMetaSnapshot, sqlx::Error> {
+    let race_meta_json = serde_json::to_string(race_meta)
+        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let created_at = Utc::now().to_rfc3339();
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO race_meta_snapshots (
+            race_id,
+            model_version,
+            params_hash,
+            created_at,
+            race_meta
+        )
+        VALUES (?, ?, ?, ?, ?);
+        "#,
+    )
+    .bind(race_id)
+    .bind(model_version)
+    .bind(params_hash)
+    .bind(&created_at)
+    .bind(&race_meta_json)
+    .execute(pool)
+    .await?;
+
+    Ok(RaceMetaSnapshot {
+        id: result.last_insert_rowid(),
+        race_id,
+        model_version: model_version.to_string(),
+        params_hash: params_hash.to_string(),
+        created_at,
+        race_meta: race_meta.clone(),
+    })
+}
+
+pub async fn snapshot_racecard(
+    pool: &SqlitePool,
+    racecard: &Racecard,
+    config: &ModelConfig,
+) -> Result<Vec<RaceMetaSnapshot>, sqlx::Error> {
+    let racecard_date = Transformers::yyyymmdd_to_mmddyyyy(&racecard.date);
+    let hash = params_hash(config);
+    let sire_stats = match &config.pedigree_switch {
+        Some(pedigree_switch) => read_sire_stats(pool, pedigree_switch.route_yards).await?,
+        None => Vec::new(),
+    };
+
+    let mut snapshots = Vec::with_capacity(racecard.races.len());
+    for race in &racecard.races {
+        let ctx = RankContext {
+            racecard_date: racecard_date.as_deref(),
+            track_code: Some(&racecard.track_code),
+            sire_stats: &sire_stats,
+            racecard: Some(racecard),
+        };
+        let race_meta = derive_race_meta_with_context(race, &ctx, config);
+        snapshots.push(add_race_meta_snapshot(pool, race.id, &race_meta, MODEL_VERSION, &hash).await?);
+    }
+
+    Ok(snapshots)
+}
+
+pub async fn read_race_meta_snapshots(
+    pool: &SqlitePool,
+    race_id: i64,
+    model_version: Option<&str>,
+) -> Result<Vec<RaceMetaSnapshot>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM race_meta_snapshots
+        WHERE race_id = ? AND (? IS NULL OR model_version = ?)
+        ORDER BY created_at, id;
+        "#,
+    )
+    .bind(race_id)
+    .bind(model_version)
+    .bind(model_version)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(race_meta_snapshot_from_row).collect()
+}
+
+pub async fn latest_race_meta_snapshot(
+    pool: &SqlitePool,
+    race_id: i64,
+    model_version: Option<&str>,
+) -> Result<Option<RaceMetaSnapshot>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM race_meta_snapshots
+        WHERE race_id = ? AND (? IS NULL OR model_version = ?)
+        ORDER BY created_at DESC, id DESC
+        LIMIT 1;
+        "#,
+    )
+    .bind(race_id)
+    .bind(model_version)
+    .bind(model_version)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(race_meta_snapshot_from_row).transpose()
+}
+
+pub async fn delete_race_meta_snapshots(
+    pool: &SqlitePool,
+    race_id: i64,
+    model_version: Option<&str>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM race_meta_snapshots WHERE race_id = ? AND (? IS NULL OR model_version = ?);")
+        .bind(race_id)
+        .bind(model_version)
+        .bind(model_version)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
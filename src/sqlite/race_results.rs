@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use sqlx::{Row, SqliteConnection, SqlitePool, sqlite::SqliteRow};
+use crate::models::race_result::{RaceResult, ResultPayout, ResultStarter};
+use crate::sqlite::racecards::{opt_f64, opt_i32, opt_i64, opt_u32};
+
+fn race_result_from_row(row: &SqliteRow) -> RaceResult {
+    RaceResult {
+        id: row.get("id"),
+        race_id: opt_i64(row, "race_id"),
+        track_code: row.get("track_code"),
+        race_date: row.get("race_date"),
+        race_number: opt_u32(row, "race_number"),
+        distance: opt_i32(row, "distance"),
+        surface: row.get("surface"),
+        track_condition: row.get("track_condition"),
+        final_time: opt_f64(row, "final_time"),
+        source_file: row.get("source_file"),
+        starters: Vec::new(),
+        payouts: Vec::new(),
+    }
+}
+
+fn result_starter_from_row(row: &SqliteRow) -> ResultStarter {
+    ResultStarter {
+        id: row.get("id"),
+        race_result_id: row.get("race_result_id"),
+        horse_id: opt_i64(row, "horse_id"),
+        program_number: row.get("program_number"),
+        horse_name: row.get("horse_name"),
+        post_position: opt_u32(row, "post_position"),
+        finish_position: opt_u32(row, "finish_position"),
+        margin: opt_f64(row, "margin"),
+        beaten_lengths: opt_f64(row, "beaten_lengths"),
+        final_odds: opt_f64(row, "final_odds"),
+        win_payoff: opt_f64(row, "win_payoff"),
+        place_payoff: opt_f64(row, "place_payoff"),
+        show_payoff: opt_f64(row, "show_payoff"),
+    }
+}
+
+fn result_payout_from_row(row: &SqliteRow) -> ResultPayout {
+    ResultPayout {
+        id: row.get("id"),
+        race_result_id: row.get("race_result_id"),
+        pool: row.get("pool"),
+        base_amount: opt_f64(row, "base_amount"),
+        winning_numbers: row.get("winning_numbers"),
+        payoff: opt_f64(row, "payoff"),
+        pool_total: opt_f64(row, "pool_total"),
+    }
+}
+
+async fn find_race_id(
+    conn: &mut SqliteConnection,
+    track_code: &str,
+    race_date: &str,
+    race_number: Option<u32>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT r.id FROM races r
+        JOIN racecards rc ON rc.id = r.racecard_id
+        WHERE lower(rc.track_code) = lower(?) AND rc.date = ? AND r.race_number IS ?
+        ORDER BY rc.id DESC
+        LIMIT 1;
+        "#,
+    )
+    .bind(track_code)
+    .bind(race_date)
+    .bind(race_number)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|r| r.get("id")))
+}
+
+async fn find_horse_id(
+    conn: &mut SqliteConnection,
+    race_id: i64,
+    starter: &ResultStarter,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id FROM horses
+        WHERE race_id = ?
+            AND (upper(trim(program_number)) = upper(trim(?)) OR upper(trim(horse_name)) = upper(trim(?)))
+        ORDER BY upper(trim(program_number)) = upper(trim(?)) DESC, id
+        LIMIT 1;
+        "#,
+    )
+    .bind(race_id)
+    .bind(&starter.program_number)
+    .bind(&starter.horse_name)
+    .bind(&starter.program_number)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|r| r.get("id")))
+}
+
+async fn link_starters(
+    conn: &mut SqliteConnection,
+    race_result_id: i64,
+    race_id: i64,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM result_starters WHERE race_result_id = ? ORDER BY id;")
+        .bind(race_result_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    for row in rows {
+        let starter = result_starter_from_row(&row);
+        let horse_id = find_horse_id(conn, race_id, &starter).await?;
+        sqlx::query("UPDATE result_starters SET horse_id = ? WHERE id = ?;")
+            .bind(horse_id)
+            .bind(starter.id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn add_race_results(
+    pool: &SqlitePool,
+    mut results: Vec<RaceResult>,
+) -> Result<Vec<RaceResult>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for result in &mut results {
+        result.race_id = find_race_id(&mut tx, &result.track_code, &result.race_date, result.race_number).await?;
+
+        sqlx::query("DELETE FROM race_results WHERE track_code = ? AND race_date = ? AND race_number IS ?;")
+            .bind(&result.track_code)
+            .bind(&result.race_date)
+            .bind(result.race_number)
+            .execute(&mut *tx)
+            .await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO race_results (
+                race_id,
+                track_code,
+                race_date,
+                race_number,
+                distance,
+                surface,
+                track_condition,
+                final_time,
+                source_file
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?);
+            "#,
+        )
+        .bind(result.race_id)
+        .bind(&result.track_code)
+        .bind(&result.race_date)
+        .bind(result.race_number)
+        .bind(result.distance)
+        .bind(&result.surface)
+        .bind(&result.track_condition)
+        .bind(result.final_time)
+        .bind(&result.source_file)
+        .execute(&mut *tx)
+        .await?;
+        result.id = inserted.last_insert_rowid();
+
+        for starter in &mut result.starters {
+            starter.race_result_id = result.id;
+            starter.horse_id = match result.race_id {
+                Some(race_id) => find_horse_id(&mut tx, race_id, starter).await?,
+                None => None,
+            };
+
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO result_starters (
+                    race_result_id,
+                    horse_id,
+                    program_number,
+                    horse_name,
+                    post_position,
+                    finish_position,
+                    margin,
+                    beaten_lengths,
+                    final_odds,
+                    win_payoff,
+                    place_payoff,
+                    show_payoff
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+                "#,
+            )
+            .bind(starter.race_result_id)
+            .bind(starter.horse_id)
+            .bind(&starter.program_number)
+            .bind(&starter.horse_name)
+            .bind(starter.post_position)
+            .bind(starter.finish_position)
+            .bind(starter.margin)
+            .bind(starter.beaten_lengths)
+            .bind(starter.final_odds)
+            .bind(starter.win_payoff)
+            .bind(starter.place_payoff)
+            .bind(starter.show_payoff)
+            .execute(&mut *tx)
+            .await?;
+            starter.id = inserted.last_insert_rowid();
+        }
+
+        for payout in &mut result.payouts {
+            payout.race_result_id = result.id;
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO result_payouts (
+                    race_result_id,
+                    pool,
+                    base_amount,
+                    winning_numbers,
+                    payoff,
+                    pool_total
+                )
+                VALUES (?, ?, ?, ?, ?, ?);
+                "#,
+            )
+            .bind(payout.race_result_id)
+            .bind(&payout.pool)
+            .bind(payout.base_amount)
+            .bind(&payout.winning_numbers)
+            .bind(payout.payoff)
+            .bind(payout.pool_total)
+            .execute(&mut *tx)
+            .await?;
+            payout.id = inserted.last_insert_rowid();
+        }
+    }
+
+    tx.commit().await?;
+    Ok(results)
+}
+
+pub async fn link_race_results(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM race_results WHERE race_id IS NULL ORDER BY id;")
+        .fetch_all(pool)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut linked = 0;
+
+    for row in rows {
+        let result = race_result_from_row(&row);
+        let race_id = match find_race_id(&mut tx, &result.track_code, &result.race_date, result.race_number).await? {
+            Some(id) => id,
+            None => continue,
+        };
+
+        sqlx::query("UPDATE race_results SET race_id = ? WHERE id = ?;")
+            .bind(race_id)
+            .bind(result.id)
+            .execute(&mut *tx)
+            .await?;
+        link_starters(&mut tx, result.id, race_id).await?;
+        linked += 1;
+    }
+
+    tx.commit().await?;
+    Ok(linked)
+}
+
+async fn attach_children(pool: &SqlitePool, results: &mut [RaceResult]) -> Result<(), sqlx::Error> {
+    if results.is_empty() {
+        return Ok(());
+    }
+
+    let placeholders = vec!["?"; results.len()].join(", ");
+
+    let starters_sql = format!("SELECT * FROM result_starters WHERE race_result_id IN ({placeholders}) ORDER BY id;");
+    let mut starters_query = sqlx::query(&starters_sql);
+    for result in results.iter() {
+        starters_query = starters_query.bind(result.id);
+    }
+    let starter_rows = starters_query.fetch_all(pool).await?;
+
+    let payouts_sql = format!("SELECT * FROM result_payouts WHERE race_result_id IN ({placeholders}) ORDER BY id;");
+    let mut payouts_query = sqlx::query(&payouts_sql);
+    for result in results.iter() {
+        payouts_query = payouts_query.bind(result.id);
+    }
+    let payout_rows = payouts_query.fetch_all(pool).await?;
+
+    let mut starters_by_result: HashMap<i64, Vec<ResultStarter>> = HashMap::new();
+    for row in starter_rows {
+        let starter = result_starter_from_row(&row);
+        starters_by_result.entry(starter.race_result_id).or_default().push(starter);
+    }
+
+    let mut payouts_by_result: HashMap<i64, Vec<ResultPayout>> = HashMap::new();
+    for row in payout_rows {
+        let payout = result_payout_from_row(&row);
+        payouts_by_result.entry(payout.race_result_id).or_default().push(payout);
+    }
+
+    for result in results.iter_mut() {
+        result.starters = starters_by_result.remove(&result.id).unwrap_or_default();
+        result.payouts = payouts_by_result.remove(&result.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+pub async fn read_race_result(pool: &SqlitePool, race_id: i64) -> Result<Option<RaceResult>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM race_results WHERE race_id = ? ORDER BY id DESC LIMIT 1;")
+        .bind(race_id)
+        .fetch_optional(pool)
+        .await?;
+
+    let mut results: Vec<RaceResult> = row.iter().map(race_result_from_row).collect();
+    attach_children(pool, &mut results).await?;
+    Ok(results.pop())
+}
+
+pub async fn read_race_results_for_racecard(
+    pool: &SqlitePool,
+    racecard_id: i64,
+) -> Result<Vec<RaceResult>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT rr.* FROM race_results rr
+        JOIN races r ON r.id = rr.race_id
+        WHERE r.racecard_id = ?
+        ORDER BY rr.race_number, rr.id;
+        "#,
+    )
+    .bind(racecard_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut results: Vec<RaceResult> = rows.iter().map(race_result_from_row).collect();
+    attach_children(pool, &mut results).await?;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::{Horse, Race, Racecard};
+    use crate::sqlite::racecards::add_racecard;
+    use crate::sqlite::test_pool;
+
+    fn starter(program_number: &str, finish_position: u32) -> ResultStarter {
+        ResultStarter {
+            program_number: program_number.to_string(),
+            horse_name: format!("Horse {program_number}"),
+            finish_position: Some(finish_position),
+            ..Default::default()
+        }
+    }
+
+    fn result(race_number: u32, starters: Vec<ResultStarter>, payouts: Vec<ResultPayout>) -> RaceResult {
+        RaceResult {
+            track_code: "sar".to_string(),
+            race_date: "20260101".to_string(),
+            race_number: Some(race_number),
+            starters,
+            payouts,
+            ..Default::default()
+        }
+    }
+
+    fn race(race_number: u32) -> Race {
+        let horses = ["1", "2"]
+            .iter()
+            .map(|p| Horse { program_number: p.to_string(), horse_name: format!("Horse {p}"), ..Default::default() })
+            .collect();
+        Race { race_number: Some(race_number), horses, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn reads_children_back_for_every_result_on_a_card() {
+        let pool = test_pool().await;
+        let card = Racecard {
+            track_code: "SAR".to_string(),
+            date: "20260101".to_string(),
+            races: vec![race(1), race(2)],
+            ..Default::default()
+        };
+        let card = add_racecard(&pool, card).await.unwrap();
+
+        let win = ResultPayout {
+            pool: "Win".to_string(),
+            winning_numbers: "1".to_string(),
+            payoff: Some(6.6),
+            ..Default::default()
+        };
+        add_race_results(
+            &pool,
+            vec![
+                result(1, vec![starter("1", 1), starter("2", 2)], vec![win]),
+                result(2, vec![starter("2", 1)], Vec::new()),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let results = read_race_results_for_racecard(&pool, card.id).await.unwrap();
+        let shape: Vec<(Option<u32>, usize, usize)> =
+            results.iter().map(|r| (r.race_number, r.starters.len(), r.payouts.len())).collect();
+        assert_eq!(shape, vec![(Some(1), 2, 1), (Some(2), 1, 0)]);
+        assert_eq!(results[0].race_id, Some(card.races[0].id));
+        assert_eq!(results[0].starters[0].horse_id, Some(card.races[0].horses[0].id));
+
+        let single = read_race_result(&pool, card.races[1].id).await.unwrap().unwrap();
+        assert_eq!(single.winners()[0].program_number, "2");
+        assert!(read_race_results_for_racecard(&pool, card.id + 1).await.unwrap().is_empty());
+    }
+}
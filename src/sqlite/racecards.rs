@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 use sqlx::{Row, SqlitePool, sqlite::SqliteRow};
 use crate::models::racecard::{
-    Horse, KeyTrainerStat, PastPerformance, Race, Racecard, Workout,
+    Horse, KeyTrainerStat, PastPerformance, Race, Racecard, RacecardSummary, Workout,
 };
+use crate::sqlite::horse_identities::{migrate_horse_identities, resolve_horse_identity};
+use crate::sqlite::trip_comment_search::{index_past_performance, index_race, migrate_trip_comment_index};
 
 const RACE_COLUMNS: usize = 43;
-const HORSE_COLUMNS: usize = 145;
+const HORSE_COLUMNS: usize = 146;
 const PAST_PERFORMANCE_COLUMNS: usize = 101;
 
 fn placeholders(count: usize) -> String {
-    std::iter::repeat("?")
-        .take(count)
+    std::iter::repeat_n("?", count)
         .collect::<Vec<_>>()
         .join(", ")
 }
@@ -77,9 +78,20 @@ pub async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         );
         "#,
         r#"
+        CREATE TABLE IF NOT EXISTS horse_identity (
+            id INTEGER PRIMARY KEY,
+            horse_name TEXT NOT NULL COLLATE NOCASE,
+            year_of_birth INTEGER,
+            dam TEXT NOT NULL COLLATE NOCASE,
+            sire TEXT NOT NULL COLLATE NOCASE,
+            UNIQUE (horse_name, year_of_birth, dam, sire)
+        );
+        "#,
+        r#"
         CREATE TABLE IF NOT EXISTS horses (
             id INTEGER PRIMARY KEY,
             race_id INTEGER NOT NULL,
+            horse_identity_id INTEGER,
             scratched INTEGER NOT NULL,
             trip_handicapping_info TEXT NOT NULL,
             post_position INTEGER,
@@ -224,7 +236,8 @@ pub async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             trainer_jockey_combo_shows_meet INTEGER,
             trainer_jockey_combo_roi_meet REAL,
             note TEXT NOT NULL,
-            FOREIGN KEY (race_id) REFERENCES races(id) ON DELETE CASCADE
+            FOREIGN KEY (race_id) REFERENCES races(id) ON DELETE CASCADE,
+            FOREIGN KEY (horse_identity_id) REFERENCES horse_identity(id)
         );
         "#,
         r#"
@@ -362,12 +375,97 @@ pub async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             FOREIGN KEY (horse_id) REFERENCES horses(id) ON DELETE CASCADE
         );
         "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS race_results (
+            id INTEGER PRIMARY KEY,
+            race_id INTEGER,
+            track_code TEXT NOT NULL,
+            race_date TEXT NOT NULL,
+            race_number INTEGER,
+            distance INTEGER,
+            surface TEXT NOT NULL,
+            track_condition TEXT NOT NULL,
+            final_time REAL,
+            source_file TEXT NOT NULL,
+            UNIQUE (track_code, race_date, race_number),
+            FOREIGN KEY (race_id) REFERENCES races(id) ON DELETE SET NULL
+        );
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS result_starters (
+            id INTEGER PRIMARY KEY,
+            race_result_id INTEGER NOT NULL,
+            horse_id INTEGER,
+            program_number TEXT NOT NULL,
+            horse_name TEXT NOT NULL,
+            post_position INTEGER,
+            finish_position INTEGER,
+            margin REAL,
+            beaten_lengths REAL,
+            final_odds REAL,
+            win_payoff REAL,
+            place_payoff REAL,
+            show_payoff REAL,
+            FOREIGN KEY (race_result_id) REFERENCES race_results(id) ON DELETE CASCADE,
+            FOREIGN KEY (horse_id) REFERENCES horses(id) ON DELETE SET NULL
+        );
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS result_payouts (
+            id INTEGER PRIMARY KEY,
+            race_result_id INTEGER NOT NULL,
+            pool TEXT NOT NULL,
+            base_amount REAL,
+            winning_numbers TEXT NOT NULL,
+            payoff REAL,
+            pool_total REAL,
+            FOREIGN KEY (race_result_id) REFERENCES race_results(id) ON DELETE CASCADE
+        );
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS race_meta_snapshots (
+            id INTEGER PRIMARY KEY,
+            race_id INTEGER NOT NULL,
+            model_version TEXT NOT NULL,
+            params_hash TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            race_meta TEXT NOT NULL,
+            FOREIGN KEY (race_id) REFERENCES races(id) ON DELETE CASCADE
+        );
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_race_meta_snapshots_race_id ON race_meta_snapshots(race_id, model_version);
+        "#,
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS trip_comment_fts USING fts5(
+            trip_comment,
+            extended_start_comment,
+            race_conditions,
+            racecard_id UNINDEXED,
+            race_id UNINDEXED,
+            horse_id UNINDEXED,
+            past_performance_id UNINDEXED,
+            track_code UNINDEXED,
+            race_date UNINDEXED,
+            surface UNINDEXED,
+            tokenize = 'porter unicode61'
+        );
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS index_versions (
+            name TEXT PRIMARY KEY,
+            version INTEGER NOT NULL
+        );
+        "#,
     ];
 
     for statement in statements {
         sqlx::query(statement).execute(pool).await?;
     }
 
+    migrate_horse_identities(pool).await?;
+    migrate_trip_comment_index(pool).await?;
+
     Ok(())
 }
 
@@ -480,15 +578,19 @@ pub async fn read_racecard(pool: &SqlitePool, racecard_row: SqliteRow) -> Result
 }
 
 
-fn opt_u32(row: &SqliteRow, col: &str) -> Option<u32> {
+pub(crate) fn opt_i64(row: &SqliteRow, col: &str) -> Option<i64> {
+    row.try_get::<Option<i64>, _>(col).ok().flatten()
+}
+
+pub(crate) fn opt_u32(row: &SqliteRow, col: &str) -> Option<u32> {
     row.try_get::<Option<i64>, _>(col).ok().flatten().and_then(|v| u32::try_from(v).ok())
 }
 
-fn opt_i32(row: &SqliteRow, col: &str) -> Option<i32> {
+pub(crate) fn opt_i32(row: &SqliteRow, col: &str) -> Option<i32> {
     row.try_get::<Option<i64>, _>(col).ok().flatten().and_then(|v| i32::try_from(v).ok())
 }
 
-fn opt_f64(row: &SqliteRow, col: &str) -> Option<f64> {
+pub(crate) fn opt_f64(row: &SqliteRow, col: &str) -> Option<f64> {
     row.try_get::<Option<f64>, _>(col).ok().flatten()
 }
 
@@ -546,6 +648,7 @@ fn horse_from_row(row: &SqliteRow) -> Horse {
     Horse {
         id: row.get("id"),
         race_id: row.get("race_id"),
+        horse_identity_id: opt_i64(row, "horse_identity_id"),
         scratched: row.get::<i64, _>("scratched") != 0,
         trip_handicapping_info: row.get("trip_handicapping_info"),
         post_position: opt_u32(row, "post_position"),
@@ -712,7 +815,7 @@ fn workout_from_row(row: &SqliteRow) -> Workout {
     }
 }
 
-fn past_performance_from_row(row: &SqliteRow) -> PastPerformance {
+pub(crate) fn past_performance_from_row(row: &SqliteRow) -> PastPerformance {
     PastPerformance {
         id: row.get("id"),
         horse_id: row.get("horse_id"),
@@ -958,13 +1061,16 @@ pub async fn add_racecard(
             .execute(&mut *tx)
             .await?;
         race.id = result.last_insert_rowid();
+        index_race(&mut tx, &racecard.track_code, &racecard.date, race).await?;
 
         for horse in &mut race.horses {
             horse.race_id = race.id;
+            horse.horse_identity_id = resolve_horse_identity(&mut tx, horse).await?;
             let horse_sql = format!(
                 r#"
                 INSERT INTO horses (
                     race_id,
+                    horse_identity_id,
                     scratched,
                     trip_handicapping_info,
                     post_position,
@@ -1116,6 +1222,7 @@ pub async fn add_racecard(
             );
             let result = sqlx::query(&horse_sql)
                 .bind(horse.race_id)
+                .bind(horse.horse_identity_id)
                 .bind(horse.scratched)
                 .bind(&horse.trip_handicapping_info)
                 .bind(horse.post_position)
@@ -1514,6 +1621,7 @@ pub async fn add_racecard(
                     .execute(&mut *tx)
                     .await?;
                 past_performance.id = result.last_insert_rowid();
+                index_past_performance(&mut tx, racecard.id, race.id, past_performance).await?;
             }
 
             for key_trainer_stat in &mut horse.key_trainer_stats {
@@ -1547,3 +1655,68 @@ pub async fn add_racecard(
     tx.commit().await?;
     Ok(racecard)
 }
+
+fn racecard_summary_from_row(row: &SqliteRow) -> RacecardSummary {
+    RacecardSummary {
+        id: row.get("id"),
+        zip_file_name: row.get("zip_file_name"),
+        track_code: row.get("track_code"),
+        track: row.get("track"),
+        date: row.get("date"),
+        long_date: row.get("long_date"),
+        race_count: opt_u32(row, "race_count").unwrap_or(0),
+    }
+}
+
+pub async fn read_racecard_by_id(pool: &SqlitePool, racecard_id: i64) -> Result<Option<Racecard>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM racecards WHERE id = ?;")
+        .bind(racecard_id)
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some(read_racecard(pool, row).await?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn list_racecards(pool: &SqlitePool) -> Result<Vec<RacecardSummary>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT rc.*, (SELECT count(*) FROM races r WHERE r.racecard_id = rc.id) AS race_count
+        FROM racecards rc
+        ORDER BY rc.date DESC, rc.id DESC;
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(racecard_summary_from_row).collect())
+}
+
+pub async fn delete_racecard(pool: &SqlitePool, racecard_id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM trip_comment_fts WHERE racecard_id = ?;")
+        .bind(racecard_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM racecards WHERE id = ?;")
+        .bind(racecard_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn update_scratch(pool: &SqlitePool, horse_id: i64, scratched: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE horses SET scratched = ? WHERE id = ?;")
+        .bind(scratched)
+        .bind(horse_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,123 @@
+use sqlx::{Row, SqlitePool};
+use crate::models::pedigree_switch::SireStats;
+use crate::sqlite::racecards::opt_u32;
+
+pub async fn read_sire_stats(pool: &SqlitePool, route_yards: i32) -> Result<Vec<SireStats>, sqlx::Error> {
+    // A horse's PP lines are repeated on every card it was entered on, so dedupe to one row per race run.
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            sire,
+            COUNT(DISTINCT horse_name) AS offspring,
+            SUM(surface = 'T') AS turf_starts,
+            SUM(surface = 'T' AND won) AS turf_wins,
+            SUM(all_weather = 'A') AS synthetic_starts,
+            SUM(all_weather = 'A' AND won) AS synthetic_wins,
+            SUM(abs(distance) >= ?1) AS route_starts,
+            SUM(abs(distance) >= ?1 AND won) AS route_wins
+        FROM (
+            SELECT DISTINCT
+                upper(trim(h.sire)) AS sire,
+                upper(trim(h.horse_name)) AS horse_name,
+                pp.race_date,
+                upper(trim(pp.track_code)) AS track_code,
+                pp.race_number,
+                upper(trim(pp.surface)) AS surface,
+                upper(trim(pp.previous_all_weather_surface_indicator)) AS all_weather,
+                pp.distance,
+                CAST(pp.finish_position AS INTEGER) = 1 AS won
+            FROM horses h
+            JOIN past_performances pp ON pp.horse_id = h.id
+            WHERE trim(h.sire) <> ''
+        )
+        GROUP BY sire
+        ORDER BY sire;
+        "#,
+    )
+    .bind(route_yards)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| SireStats {
+            sire: row.get("sire"),
+            offspring: opt_u32(row, "offspring").unwrap_or(0),
+            turf_starts: opt_u32(row, "turf_starts").unwrap_or(0),
+            turf_wins: opt_u32(row, "turf_wins").unwrap_or(0),
+            synthetic_starts: opt_u32(row, "synthetic_starts").unwrap_or(0),
+            synthetic_wins: opt_u32(row, "synthetic_wins").unwrap_or(0),
+            route_starts: opt_u32(row, "route_starts").unwrap_or(0),
+            route_wins: opt_u32(row, "route_wins").unwrap_or(0),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::{Horse, PastPerformance, Race, Racecard};
+    use crate::sqlite::racecards::add_racecard;
+    use crate::sqlite::test_pool;
+
+    fn start(race_date: &str, surface: &str, distance: i32, finish_position: &str) -> PastPerformance {
+        PastPerformance {
+            race_date: race_date.to_string(),
+            track_code: "SAR".to_string(),
+            race_number: Some(1),
+            surface: surface.to_string(),
+            distance: Some(distance),
+            finish_position: finish_position.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn racecard(date: &str, horses: Vec<Horse>) -> Racecard {
+        Racecard {
+            track_code: "SAR".to_string(),
+            date: date.to_string(),
+            races: vec![Race { race_number: Some(1), horses, ..Default::default() }],
+            ..Default::default()
+        }
+    }
+
+    fn horse(name: &str, sire: &str, past_performances: Vec<PastPerformance>) -> Horse {
+        Horse {
+            horse_name: name.to_string(),
+            program_number: "1".to_string(),
+            sire: sire.to_string(),
+            past_performances,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_each_offspring_start_once_across_cards() {
+        let pool = test_pool().await;
+        let starts = vec![start("12/01/2025", "T", 1760, "1"), start("11/01/2025", "D", 1650, "3")];
+        for date in ["20260101", "20260201"] {
+            add_racecard(&pool, racecard(date, vec![horse("Foo", "Bar Sire", starts.clone())])).await.unwrap();
+        }
+        let sibling = horse("Baz", " bar sire", vec![start("12/05/2025", "T", 1320, "2")]);
+        add_racecard(&pool, racecard("20260301", vec![sibling])).await.unwrap();
+
+        let stats = read_sire_stats(&pool, 1760).await.unwrap();
+
+        assert_eq!(stats.len(), 1);
+        let sire = &stats[0];
+        assert_eq!(sire.sire, "BAR SIRE");
+        assert_eq!(sire.offspring, 2);
+        assert_eq!((sire.turf_starts, sire.turf_wins), (2, 1));
+        assert_eq!((sire.route_starts, sire.route_wins), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn route_starts_follow_the_cutoff() {
+        let pool = test_pool().await;
+        let starts = vec![start("12/01/2025", "D", 1760, "1"), start("11/01/2025", "D", 1650, "1")];
+        add_racecard(&pool, racecard("20260101", vec![horse("Foo", "Bar", starts)])).await.unwrap();
+
+        assert_eq!(read_sire_stats(&pool, 1760).await.unwrap()[0].route_starts, 1);
+        assert_eq!(read_sire_stats(&pool, 1600).await.unwrap()[0].route_starts, 2);
+    }
+}
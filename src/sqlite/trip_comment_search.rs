@@ -0,0 +1,419 @@
+use chrono::NaiveDate;
+use sqlx::{Row, SqliteConnection, SqlitePool, sqlite::SqliteRow};
+use crate::models::racecard::{PastPerformance, Race};
+use crate::models::trip_comment_search::{TripCommentHit, TripCommentSearchFilters};
+use crate::sqlite::racecards::{opt_f64, opt_i64, opt_u32};
+
+// Bumped when the indexed columns change, so existing databases rebuild once.
+const TRIP_COMMENT_INDEX_VERSION: i64 = 2;
+
+fn parse_date(value: &str) -> Option<String> {
+    let v = value.trim();
+    let parsed = if v.len() == 8 && v.chars().all(|c| c.is_ascii_digit()) {
+        NaiveDate::parse_from_str(v, "%Y%m%d").ok()
+    } else {
+        NaiveDate::parse_from_str(v, "%m/%d/%Y").ok()
+    };
+    parsed.map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// Search filters are user input, so a bad date is an error rather than a silently empty result.
+fn iso_date(value: &str) -> Result<String, sqlx::Error> {
+    parse_date(value).ok_or_else(|| sqlx::Error::Encode(format!("invalid date `{value}`").into()))
+}
+
+fn trip_comment_hit_from_row(row: &SqliteRow) -> TripCommentHit {
+    TripCommentHit {
+        racecard_id: row.get("racecard_id"),
+        race_id: row.get("race_id"),
+        race_number: opt_u32(row, "race_number"),
+        horse_id: opt_i64(row, "horse_id"),
+        horse_name: row.try_get::<Option<String>, _>("horse_name").ok().flatten().unwrap_or_default(),
+        program_number: row.try_get::<Option<String>, _>("program_number").ok().flatten().unwrap_or_default(),
+        past_performance_id: opt_i64(row, "past_performance_id"),
+        track_code: row.get("track_code"),
+        race_date: row.try_get::<Option<String>, _>("race_date").ok().flatten().unwrap_or_default(),
+        surface: row.get("surface"),
+        trip_comment: row.get("trip_comment"),
+        extended_start_comment: row.get("extended_start_comment"),
+        race_conditions: row.get("race_conditions"),
+        rank: opt_f64(row, "rank").unwrap_or(0.0),
+    }
+}
+
+pub(crate) async fn index_race(
+    conn: &mut SqliteConnection,
+    track_code: &str,
+    racecard_date: &str,
+    race: &Race,
+) -> Result<(), sqlx::Error> {
+    if race.race_conditions.trim().is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO trip_comment_fts (
+            trip_comment,
+            extended_start_comment,
+            race_conditions,
+            racecard_id,
+            race_id,
+            horse_id,
+            past_performance_id,
+            track_code,
+            race_date,
+            surface
+        )
+        VALUES ('', '', ?, ?, ?, NULL, NULL, ?, ?, ?);
+        "#,
+    )
+    .bind(&race.race_conditions)
+    .bind(race.racecard_id)
+    .bind(race.id)
+    .bind(track_code.to_uppercase())
+    .bind(parse_date(racecard_date))
+    .bind(&race.surface)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn index_past_performance(
+    conn: &mut SqliteConnection,
+    racecard_id: i64,
+    race_id: i64,
+    past_performance: &PastPerformance,
+) -> Result<(), sqlx::Error> {
+    if past_performance.trip_comment.trim().is_empty()
+        && past_performance.extended_start_comment.trim().is_empty()
+    {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO trip_comment_fts (
+            trip_comment,
+            extended_start_comment,
+            race_conditions,
+            racecard_id,
+            race_id,
+            horse_id,
+            past_performance_id,
+            track_code,
+            race_date,
+            surface
+        )
+        VALUES (?, ?, '', ?, ?, ?, ?, ?, ?, ?);
+        "#,
+    )
+    .bind(&past_performance.trip_comment)
+    .bind(&past_performance.extended_start_comment)
+    .bind(racecard_id)
+    .bind(race_id)
+    .bind(past_performance.horse_id)
+    .bind(past_performance.id)
+    .bind(past_performance.track_code.to_uppercase())
+    .bind(parse_date(&past_performance.race_date))
+    .bind(&past_performance.surface)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn rebuild_trip_comment_index(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM trip_comment_fts;")
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO trip_comment_fts (
+            trip_comment,
+            extended_start_comment,
+            race_conditions,
+            racecard_id,
+            race_id,
+            horse_id,
+            past_performance_id,
+            track_code,
+            race_date,
+            surface
+        )
+        SELECT
+            '',
+            '',
+            r.race_conditions,
+            r.racecard_id,
+            r.id,
+            NULL,
+            NULL,
+            upper(rc.track_code),
+            CASE WHEN rc.date GLOB '[0-9][0-9][0-9][0-9][0-9][0-9][0-9][0-9]'
+                THEN substr(rc.date, 1, 4) || '-' || substr(rc.date, 5, 2) || '-' || substr(rc.date, 7, 2)
+            END,
+            r.surface
+        FROM races r
+        JOIN racecards rc ON rc.id = r.racecard_id
+        WHERE trim(r.race_conditions) <> '';
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO trip_comment_fts (
+            trip_comment,
+            extended_start_comment,
+            race_conditions,
+            racecard_id,
+            race_id,
+            horse_id,
+            past_performance_id,
+            track_code,
+            race_date,
+            surface
+        )
+        SELECT
+            pp.trip_comment,
+            pp.extended_start_comment,
+            '',
+            r.racecard_id,
+            r.id,
+            h.id,
+            pp.id,
+            upper(pp.track_code),
+            CASE WHEN pp.race_date GLOB '[0-9][0-9]/[0-9][0-9]/[0-9][0-9][0-9][0-9]'
+                THEN substr(pp.race_date, 7, 4) || '-' || substr(pp.race_date, 1, 2) || '-' || substr(pp.race_date, 4, 2)
+            END,
+            pp.surface
+        FROM past_performances pp
+        JOIN horses h ON h.id = pp.horse_id
+        JOIN races r ON r.id = h.race_id
+        WHERE trim(pp.trip_comment) <> '' OR trim(pp.extended_start_comment) <> '';
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn migrate_trip_comment_index(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let version: Option<i64> = sqlx::query("SELECT version FROM index_versions WHERE name = 'trip_comment_fts';")
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.get("version"));
+
+    if version == Some(TRIP_COMMENT_INDEX_VERSION) {
+        return Ok(());
+    }
+
+    rebuild_trip_comment_index(pool).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO index_versions (name, version) VALUES ('trip_comment_fts', ?)
+        ON CONFLICT (name) DO UPDATE SET version = excluded.version;
+        "#,
+    )
+    .bind(TRIP_COMMENT_INDEX_VERSION)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn search_trip_comments(
+    pool: &SqlitePool,
+    query: &str,
+    filters: &TripCommentSearchFilters,
+) -> Result<Vec<TripCommentHit>, sqlx::Error> {
+    let from_date = filters.from_date.as_deref().map(iso_date).transpose()?;
+    let to_date = filters.to_date.as_deref().map(iso_date).transpose()?;
+    let track_code = filters.track_code.as_ref().map(|t| t.trim().to_uppercase());
+    let surface = filters.surface.as_ref().map(|s| s.trim().to_string());
+
+    // A PP line is repeated on every card the horse was entered on, so keep one hit per race run,
+    // taken from the latest card.
+    let rows = sqlx::query(
+        r#"
+        WITH matched AS MATERIALIZED (
+            SELECT
+                rowid,
+                racecard_id,
+                race_id,
+                horse_id,
+                past_performance_id,
+                track_code,
+                race_date,
+                surface,
+                trip_comment,
+                extended_start_comment,
+                race_conditions,
+                bm25(trip_comment_fts) AS rank
+            FROM trip_comment_fts
+            WHERE trip_comment_fts MATCH ?
+        )
+        SELECT * FROM (
+            SELECT
+                f.racecard_id,
+                f.race_id,
+                r.race_number,
+                f.horse_id,
+                h.horse_name,
+                h.program_number,
+                f.past_performance_id,
+                f.track_code,
+                f.race_date,
+                f.surface,
+                f.trip_comment,
+                f.extended_start_comment,
+                f.race_conditions,
+                f.rank,
+                ROW_NUMBER() OVER (
+                    PARTITION BY CASE
+                        WHEN pp.id IS NULL THEN 'race:' || f.rowid
+                        ELSE upper(trim(h.horse_name)) || '|' || upper(trim(pp.track_code)) || '|'
+                            || pp.race_date || '|' || ifnull(pp.race_number, '')
+                    END
+                    ORDER BY f.racecard_id DESC
+                ) AS copy
+            FROM matched f
+            JOIN races r ON r.id = f.race_id
+            LEFT JOIN horses h ON h.id = f.horse_id
+            LEFT JOIN past_performances pp ON pp.id = f.past_performance_id
+            WHERE (? IS NULL OR f.track_code = ?)
+                AND (? IS NULL OR f.race_date >= ?)
+                AND (? IS NULL OR f.race_date <= ?)
+                AND (? IS NULL OR upper(f.surface) = upper(?))
+                AND (? IS NULL OR h.horse_identity_id = ?)
+        )
+        WHERE copy = 1
+        ORDER BY rank, race_date DESC
+        LIMIT ?;
+        "#,
+    )
+    .bind(query)
+    .bind(&track_code)
+    .bind(&track_code)
+    .bind(&from_date)
+    .bind(&from_date)
+    .bind(&to_date)
+    .bind(&to_date)
+    .bind(&surface)
+    .bind(&surface)
+    .bind(filters.horse_identity_id)
+    .bind(filters.horse_identity_id)
+    .bind(filters.limit.unwrap_or(100) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(trip_comment_hit_from_row).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::{Horse, Racecard};
+    use crate::sqlite::racecards::{add_racecard, create_tables};
+    use crate::sqlite::test_pool;
+
+    fn racecard(date: &str, pp_date: &str) -> Racecard {
+        let pp = PastPerformance {
+            race_date: pp_date.to_string(),
+            track_code: "SAR".to_string(),
+            race_number: Some(4),
+            surface: "D".to_string(),
+            trip_comment: "steadied sharply far turn".to_string(),
+            ..Default::default()
+        };
+        let horse = Horse {
+            horse_name: "Foo".to_string(),
+            year_of_birth: Some(2022),
+            dam: "Bar".to_string(),
+            sire: "Baz".to_string(),
+            program_number: "1".to_string(),
+            past_performances: vec![pp],
+            ..Default::default()
+        };
+        Racecard {
+            track_code: "SAR".to_string(),
+            date: date.to_string(),
+            races: vec![Race {
+                race_number: Some(1),
+                race_conditions: "For maidens, two years old".to_string(),
+                horses: vec![horse],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn no_filters() -> TripCommentSearchFilters {
+        TripCommentSearchFilters {
+            track_code: None,
+            surface: None,
+            from_date: None,
+            to_date: None,
+            horse_identity_id: None,
+            limit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn indexes_blank_and_bad_dates_without_failing_the_insert() {
+        let pool = test_pool().await;
+        add_racecard(&pool, racecard("20260101", "")).await.unwrap();
+        add_racecard(&pool, racecard("2026-01-0x", "13/45/2025")).await.unwrap();
+
+        let hits = search_trip_comments(&pool, "steadied", &no_filters()).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.race_date.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn returns_a_repeated_pp_comment_once() {
+        let pool = test_pool().await;
+        add_racecard(&pool, racecard("20260101", "12/01/2025")).await.unwrap();
+        let latest = add_racecard(&pool, racecard("20260201", "12/01/2025")).await.unwrap();
+
+        let hits = search_trip_comments(&pool, "steadied", &no_filters()).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].racecard_id, latest.id);
+        assert_eq!(hits[0].race_date, "2025-12-01");
+
+        let maidens = search_trip_comments(&pool, "maidens", &no_filters()).await.unwrap();
+        assert_eq!(maidens.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_filter_dates() {
+        let pool = test_pool().await;
+        let filters = TripCommentSearchFilters { from_date: Some("2026-13-01".to_string()), ..no_filters() };
+        assert!(search_trip_comments(&pool, "steadied", &filters).await.is_err());
+
+        let filters = TripCommentSearchFilters { from_date: Some("20251201".to_string()), ..no_filters() };
+        assert!(search_trip_comments(&pool, "steadied", &filters).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_tables_rebuilds_the_index_only_once() {
+        let pool = test_pool().await;
+        add_racecard(&pool, racecard("20260101", "12/01/2025")).await.unwrap();
+
+        sqlx::query("DELETE FROM trip_comment_fts;").execute(&pool).await.unwrap();
+        create_tables(&pool).await.unwrap();
+        assert!(search_trip_comments(&pool, "steadied", &no_filters()).await.unwrap().is_empty());
+
+        sqlx::query("DELETE FROM index_versions;").execute(&pool).await.unwrap();
+        create_tables(&pool).await.unwrap();
+        assert_eq!(search_trip_comments(&pool, "steadied", &no_filters()).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Mutex;
+use crate::models::racecard::{Horse, Racecard, RacecardSummary};
+use crate::sqlite::horse_identities::has_pedigree;
+use crate::store::RacecardStore;
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    next_id: i64,
+    racecards: BTreeMap<i64, Racecard>,
+    horse_identities: BTreeMap<IdentityKey, i64>,
+}
+
+// Same match as the SQLite store: trimmed, case-insensitive name, year of birth, dam and sire.
+type IdentityKey = (String, Option<u32>, String, String);
+
+fn identity_key(horse: &Horse) -> IdentityKey {
+    (
+        horse.horse_name.trim().to_lowercase(),
+        horse.year_of_birth,
+        horse.dam.trim().to_lowercase(),
+        horse.sire.trim().to_lowercase(),
+    )
+}
+
+impl MemoryState {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn resolve_horse_identity(&mut self, horse: &Horse) -> Option<i64> {
+        if !has_pedigree(&horse.dam, &horse.sire) {
+            return None;
+        }
+        let key = identity_key(horse);
+        if let Some(&id) = self.horse_identities.get(&key) {
+            return Some(id);
+        }
+        let id = self.next_id();
+        self.horse_identities.insert(key, id);
+        Some(id)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryRacecardStore {
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryRacecardStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_state<T>(&self, f: impl FnOnce(&mut MemoryState) -> T) -> T {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut state)
+    }
+}
+
+impl RacecardStore for MemoryRacecardStore {
+    type Error = Infallible;
+
+    async fn insert_racecard(&self, mut racecard: Racecard) -> Result<Racecard, Self::Error> {
+        Ok(self.with_state(|state| {
+            racecard.id = state.next_id();
+
+            for race in &mut racecard.races {
+                race.id = state.next_id();
+                race.racecard_id = racecard.id;
+
+                for horse in &mut race.horses {
+                    horse.id = state.next_id();
+                    horse.race_id = race.id;
+                    horse.horse_identity_id = state.resolve_horse_identity(horse);
+
+                    for workout in &mut horse.workouts {
+                        workout.id = state.next_id();
+                        workout.horse_id = horse.id;
+                    }
+                    for past_performance in &mut horse.past_performances {
+                        past_performance.id = state.next_id();
+                        past_performance.horse_id = horse.id;
+                    }
+                    for key_trainer_stat in &mut horse.key_trainer_stats {
+                        key_trainer_stat.id = state.next_id();
+                        key_trainer_stat.horse_id = horse.id;
+                    }
+                }
+            }
+
+            state.racecards.insert(racecard.id, racecard.clone());
+            racecard
+        }))
+    }
+
+    async fn read_racecard(&self, racecard_id: i64) -> Result<Option<Racecard>, Self::Error> {
+        Ok(self.with_state(|state| state.racecards.get(&racecard_id).cloned()))
+    }
+
+    async fn list_racecards(&self) -> Result<Vec<RacecardSummary>, Self::Error> {
+        Ok(self.with_state(|state| {
+            let mut summaries: Vec<RacecardSummary> = state
+                .racecards
+                .values()
+                .map(|rc| RacecardSummary {
+                    id: rc.id,
+                    zip_file_name: rc.zip_file_name.clone(),
+                    track_code: rc.track_code.clone(),
+                    track: rc.track.clone(),
+                    date: rc.date.clone(),
+                    long_date: rc.long_date.clone(),
+                    race_count: rc.races.len() as u32,
+                })
+                .collect();
+            summaries.sort_by(|a, b| b.date.cmp(&a.date).then(b.id.cmp(&a.id)));
+            summaries
+        }))
+    }
+
+    async fn delete_racecard(&self, racecard_id: i64) -> Result<bool, Self::Error> {
+        Ok(self.with_state(|state| state.racecards.remove(&racecard_id).is_some()))
+    }
+
+    async fn update_scratch(&self, horse_id: i64, scratched: bool) -> Result<bool, Self::Error> {
+        Ok(self.with_state(|state| {
+            state
+                .racecards
+                .values_mut()
+                .flat_map(|rc| rc.races.iter_mut())
+                .flat_map(|race| race.horses.iter_mut())
+                .find(|horse| horse.id == horse_id)
+                .map(|horse| horse.scratched = scratched)
+                .is_some()
+        }))
+    }
+}
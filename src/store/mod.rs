@@ -0,0 +1,94 @@
+pub mod memory;
+pub mod sqlite;
+
+use std::future::Future;
+use crate::models::racecard::{Racecard, RacecardSummary};
+
+pub trait RacecardStore {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn insert_racecard(&self, racecard: Racecard) -> impl Future<Output = Result<Racecard, Self::Error>> + Send;
+
+    fn read_racecard(&self, racecard_id: i64) -> impl Future<Output = Result<Option<Racecard>, Self::Error>> + Send;
+
+    fn list_racecards(&self) -> impl Future<Output = Result<Vec<RacecardSummary>, Self::Error>> + Send;
+
+    fn delete_racecard(&self, racecard_id: i64) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+
+    fn update_scratch(&self, horse_id: i64, scratched: bool) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::racecard::{Horse, Race};
+    use crate::store::memory::MemoryRacecardStore;
+    use crate::store::sqlite::SqliteRacecardStore;
+
+    fn horse(program_number: &str, name: &str, dam: &str, sire: &str) -> Horse {
+        Horse {
+            program_number: program_number.to_string(),
+            horse_name: name.to_string(),
+            year_of_birth: Some(2022),
+            dam: dam.to_string(),
+            sire: sire.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn racecard(date: &str, horses: Vec<Horse>) -> Racecard {
+        Racecard {
+            track_code: "SAR".to_string(),
+            track: "Saratoga".to_string(),
+            date: date.to_string(),
+            races: vec![Race { race_number: Some(1), horses, ..Default::default() }],
+            ..Default::default()
+        }
+    }
+
+    async fn exercise_store(store: impl RacecardStore) {
+        let first = store
+            .insert_racecard(racecard(
+                "20260101",
+                vec![horse("1", "Foo", "Bar", "Baz"), horse("2", "Nobody", "", "")],
+            ))
+            .await
+            .unwrap();
+        let second = store
+            .insert_racecard(racecard("20260102", vec![horse("1", "FOO", "bar", "BAZ")]))
+            .await
+            .unwrap();
+
+        let foo = &first.races[0].horses[0];
+        assert!(first.id > 0 && foo.id > 0);
+        assert_eq!(foo.race_id, first.races[0].id);
+        assert!(foo.horse_identity_id.is_some());
+        assert_eq!(second.races[0].horses[0].horse_identity_id, foo.horse_identity_id);
+        assert_eq!(first.races[0].horses[1].horse_identity_id, None);
+
+        let read = store.read_racecard(first.id).await.unwrap().unwrap();
+        assert_eq!(read.races[0].horses.len(), 2);
+        assert_eq!(read.races[0].horses[0].horse_identity_id, foo.horse_identity_id);
+
+        let dates: Vec<String> = store.list_racecards().await.unwrap().into_iter().map(|s| s.date).collect();
+        assert_eq!(dates, vec!["20260102", "20260101"]);
+
+        assert!(store.update_scratch(foo.id, true).await.unwrap());
+        let read = store.read_racecard(first.id).await.unwrap().unwrap();
+        assert!(read.races[0].horses[0].scratched);
+
+        assert!(store.delete_racecard(first.id).await.unwrap());
+        assert!(store.read_racecard(first.id).await.unwrap().is_none());
+        assert!(!store.delete_racecard(first.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn memory_store() {
+        exercise_store(MemoryRacecardStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_store() {
+        exercise_store(SqliteRacecardStore::new(crate::sqlite::test_pool().await)).await;
+    }
+}
@@ -0,0 +1,43 @@
+use sqlx::SqlitePool;
+use crate::models::racecard::{Racecard, RacecardSummary};
+use crate::sqlite::racecards;
+use crate::store::RacecardStore;
+
+#[derive(Debug, Clone)]
+pub struct SqliteRacecardStore {
+    pool: SqlitePool,
+}
+
+impl SqliteRacecardStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+impl RacecardStore for SqliteRacecardStore {
+    type Error = sqlx::Error;
+
+    async fn insert_racecard(&self, racecard: Racecard) -> Result<Racecard, Self::Error> {
+        racecards::add_racecard(&self.pool, racecard).await
+    }
+
+    async fn read_racecard(&self, racecard_id: i64) -> Result<Option<Racecard>, Self::Error> {
+        racecards::read_racecard_by_id(&self.pool, racecard_id).await
+    }
+
+    async fn list_racecards(&self) -> Result<Vec<RacecardSummary>, Self::Error> {
+        racecards::list_racecards(&self.pool).await
+    }
+
+    async fn delete_racecard(&self, racecard_id: i64) -> Result<bool, Self::Error> {
+        racecards::delete_racecard(&self.pool, racecard_id).await
+    }
+
+    async fn update_scratch(&self, horse_id: i64, scratched: bool) -> Result<bool, Self::Error> {
+        racecards::update_scratch(&self.pool, horse_id, scratched).await
+    }
+}